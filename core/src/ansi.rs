@@ -0,0 +1,146 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Any escape sequence: CSI (`\x1b[...X`), OSC (`\x1b]...BEL`) or a two byte escape.
+static ESCAPE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\x1b(\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(\x07|\x1b\\)|[@-Z\\-_])")
+        .expect("Invalid regex")
+});
+
+/// The 16 basic colours, following the GNOME terminal palette.
+const PALETTE: [(u8, u8, u8); 16] = [
+    (0x24, 0x1f, 0x31),
+    (0xc0, 0x1c, 0x28),
+    (0x2e, 0xc2, 0x7e),
+    (0xf5, 0xc2, 0x11),
+    (0x1e, 0x78, 0xe4),
+    (0x98, 0x41, 0xbb),
+    (0x0a, 0xb9, 0xdc),
+    (0xc0, 0xbf, 0xbc),
+    (0x5e, 0x5c, 0x64),
+    (0xed, 0x33, 0x3b),
+    (0x57, 0xe3, 0x89),
+    (0xf8, 0xe4, 0x5c),
+    (0x51, 0xa1, 0xff),
+    (0xc0, 0x61, 0xcb),
+    (0x4f, 0xd2, 0xfd),
+    (0xf6, 0xf5, 0xf4),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl Color {
+    fn indexed(index: u8) -> Self {
+        match index {
+            0..=15 => {
+                let (r, g, b) = PALETTE[index as usize];
+                Color(r, g, b)
+            }
+            16..=231 => {
+                let index = index - 16;
+                let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+                Color(level(index / 36), level(index / 6 % 6), level(index % 6))
+            }
+            _ => {
+                let level = 8 + (index - 232) * 10;
+                Color(level, level, level)
+            }
+        }
+    }
+
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// Text attributes set by SGR escapes, carried from one line to the next.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Style {
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+}
+
+impl Style {
+    fn apply(&mut self, params: &str) {
+        let codes: Vec<u16> = params
+            .split([';', ':'])
+            .map(|code| code.parse().unwrap_or(0))
+            .collect();
+        let mut codes = codes.into_iter();
+
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                30..=37 => self.foreground = Some(Color::indexed(code as u8 - 30)),
+                38 => self.foreground = extended_color(&mut codes),
+                39 => self.foreground = None,
+                40..=47 => self.background = Some(Color::indexed(code as u8 - 40)),
+                48 => self.background = extended_color(&mut codes),
+                49 => self.background = None,
+                90..=97 => self.foreground = Some(Color::indexed(code as u8 - 90 + 8)),
+                100..=107 => self.background = Some(Color::indexed(code as u8 - 100 + 8)),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Reads the rest of a `38;5;n` or `38;2;r;g;b` sequence.
+fn extended_color(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    let mut next = || codes.next().map(|code| code.min(255) as u8);
+    match next()? {
+        5 => Some(Color::indexed(next()?)),
+        2 => Some(Color(next()?, next()?, next()?)),
+        _ => None,
+    }
+}
+
+/// Splits `text` into runs of the same style, starting from `style` and leaving it as the last
+/// escape set it. Escapes other than SGR are dropped.
+pub fn parse(text: &str, style: &mut Style) -> Vec<(String, Style)> {
+    let mut spans: Vec<(String, Style)> = Vec::new();
+    let mut push = |value: &str, style: &Style| {
+        if value.is_empty() {
+            return;
+        }
+        match spans.last_mut() {
+            Some((last, last_style)) if last_style == style => last.push_str(value),
+            _ => spans.push((value.to_string(), style.clone())),
+        }
+    };
+
+    let mut position = 0;
+    for escape in ESCAPE.find_iter(text) {
+        push(&text[position..escape.start()], style);
+        let sequence = escape.as_str();
+        if let Some(params) = sequence
+            .strip_prefix("\x1b[")
+            .and_then(|value| value.strip_suffix('m'))
+        {
+            style.apply(params);
+        }
+        position = escape.end();
+    }
+    push(&text[position..], style);
+    spans
+}
+
+/// Removes every escape sequence from `text`.
+pub fn strip(text: &str) -> String {
+    ESCAPE.replace_all(text, "").to_string()
+}
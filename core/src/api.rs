@@ -0,0 +1,56 @@
+use anyhow::{Result, anyhow};
+use serde::de::DeserializeOwned;
+
+use super::command::Cmd;
+
+const ARCHIVE: &str = "/tmp/proton-ge.tar.gz";
+
+/// The body of `url`, or the error the server answered with.
+pub fn download(url: &str) -> Result<Vec<u8>> {
+    let client = reqwest::blocking::Client::new();
+    let resp = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "*/*")
+        .header(reqwest::header::USER_AGENT, "PackageManager/1.0.0")
+        .send()?;
+
+    if resp.status().is_success() {
+        Ok(resp.bytes()?.to_vec())
+    } else {
+        Err(anyhow!(resp.text()?))
+    }
+}
+
+pub fn parse<T: DeserializeOwned>(body: &[u8]) -> Result<T> {
+    serde_json::from_slice::<T>(body).map_err(|e| {
+        anyhow!(
+            "Failed to parse success response: {e}\nBody: {}",
+            String::from_utf8_lossy(body)
+        )
+    })
+}
+
+pub fn get_str(url: &str) -> Result<String> {
+    let client = reqwest::blocking::Client::new();
+    let resp = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "*/*")
+        .header(reqwest::header::USER_AGENT, "PackageManager/1.0.0")
+        .send()?;
+
+    let resp = resp.text()?;
+
+    Ok(resp)
+}
+
+pub fn download_and_extract_command(url: &str, file_path: &str) -> Cmd {
+    Cmd::chain(&[
+        // The log goes to stdout so it isn't shown as errors
+        Cmd::new("wget")
+            .args(["--progress=dot:mega", "-o", "/dev/stdout", "-O", ARCHIVE])
+            .arg(url),
+        Cmd::new("tar")
+            .args(["-xvzf", ARCHIVE, "-C"])
+            .arg(file_path),
+    ])
+}
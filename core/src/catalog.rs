@@ -0,0 +1,180 @@
+use std::{
+    path::Path,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{
+    package::{PackageData, PackageStatus},
+    utils,
+};
+
+/// Bumped whenever the tables change, the cache is then dropped and filled again.
+const SCHEMA_VERSION: i64 = 1;
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS providers (
+        name TEXT PRIMARY KEY,
+        refreshed INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS packages (
+        provider TEXT NOT NULL,
+        qualified_name TEXT NOT NULL,
+        name TEXT NOT NULL,
+        status TEXT NOT NULL,
+        installed_version TEXT,
+        available_version TEXT,
+        repository TEXT NOT NULL,
+        description TEXT,
+        installed_size INTEGER,
+        download_size INTEGER,
+        licenses TEXT NOT NULL,
+        url TEXT,
+        packager TEXT,
+        install_date INTEGER,
+        popularity REAL
+    );
+    CREATE INDEX IF NOT EXISTS packages_provider ON packages (provider);
+";
+
+/// The packages of a provider as they were last loaded.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub packages: Vec<PackageData>,
+    pub refreshed: SystemTime,
+}
+
+/// Keeps the last package list of each provider, so it can be shown before the providers load
+/// and browsed while they can't, e.g. offline.
+pub struct Catalog {
+    connection: Connection,
+}
+
+impl Catalog {
+    /// Opens `catalog.db` in [`utils::system_path`].
+    pub fn open() -> Result<Self> {
+        let mut path = utils::system_path()?;
+        path.push("catalog.db");
+        Catalog::open_at(path)
+    }
+
+    pub fn open_at(path: impl AsRef<Path>) -> Result<Self> {
+        let connection = Connection::open(path)?;
+        let version: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version != SCHEMA_VERSION {
+            connection.execute_batch(
+                "DROP TABLE IF EXISTS packages;
+                DROP TABLE IF EXISTS providers;",
+            )?;
+            connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        }
+        connection.execute_batch(SCHEMA)?;
+        Ok(Catalog { connection })
+    }
+
+    /// Replaces the packages kept for `provider`, returning when they were refreshed.
+    pub fn save(&mut self, provider: &str, packages: &[PackageData]) -> Result<SystemTime> {
+        let refreshed = SystemTime::now();
+        let transaction = self.connection.transaction()?;
+        transaction.execute("DELETE FROM packages WHERE provider = ?1", [provider])?;
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO packages (provider, qualified_name, name, status, installed_version,
+                    available_version, repository, description, installed_size, download_size,
+                    licenses, url, packager, install_date, popularity)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            )?;
+            for package in packages {
+                insert.execute(params![
+                    provider,
+                    package.qualified_name,
+                    package.name,
+                    package.status.to_string(),
+                    package.installed_version,
+                    package.available_version,
+                    package.repository,
+                    package.description,
+                    package.installed_size.map(to_integer),
+                    package.download_size.map(to_integer),
+                    package.licenses.join("\n"),
+                    package.url,
+                    package.packager,
+                    package.install_date,
+                    package.popularity,
+                ])?;
+            }
+        }
+        transaction.execute(
+            "INSERT INTO providers (name, refreshed) VALUES (?1, ?2)
+            ON CONFLICT (name) DO UPDATE SET refreshed = excluded.refreshed",
+            params![provider, to_seconds(refreshed)],
+        )?;
+        transaction.commit()?;
+        Ok(refreshed)
+    }
+
+    /// The packages kept for `provider`, `None` when it was never loaded.
+    pub fn load(&self, provider: &str) -> Result<Option<Snapshot>> {
+        let refreshed: Option<i64> = self
+            .connection
+            .query_row(
+                "SELECT refreshed FROM providers WHERE name = ?1",
+                [provider],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(refreshed) = refreshed else {
+            return Ok(None);
+        };
+
+        let mut select = self.connection.prepare(
+            "SELECT qualified_name, name, status, installed_version, available_version, repository,
+                description, installed_size, download_size, licenses, url, packager, install_date,
+                popularity
+            FROM packages WHERE provider = ?1",
+        )?;
+        let packages = select
+            .query_map([provider], |row| {
+                let status: String = row.get(2)?;
+                let licenses: String = row.get(9)?;
+                Ok(PackageData {
+                    qualified_name: row.get(0)?,
+                    name: row.get(1)?,
+                    status: PackageStatus::from_str(&status).unwrap_or_default(),
+                    installed_version: row.get(3)?,
+                    available_version: row.get(4)?,
+                    repository: row.get(5)?,
+                    description: row.get(6)?,
+                    installed_size: row.get::<_, Option<i64>>(7)?.map(from_integer),
+                    download_size: row.get::<_, Option<i64>>(8)?.map(from_integer),
+                    licenses: licenses.lines().map(str::to_string).collect(),
+                    url: row.get(10)?,
+                    packager: row.get(11)?,
+                    install_date: row.get(12)?,
+                    popularity: row.get(13)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<PackageData>>>()?;
+
+        Ok(Some(Snapshot {
+            packages,
+            refreshed: UNIX_EPOCH + Duration::from_secs(refreshed.max(0) as u64),
+        }))
+    }
+}
+
+/// SQLite integers are signed.
+fn to_integer(size: u64) -> i64 {
+    i64::try_from(size).unwrap_or(i64::MAX)
+}
+
+fn from_integer(size: i64) -> u64 {
+    size.max(0) as u64
+}
+
+fn to_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
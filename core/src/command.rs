@@ -0,0 +1,822 @@
+use anyhow::{anyhow, Context, Result};
+use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
+use regex::Regex;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    Arc, Mutex,
+};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{
+    ansi,
+    error::ProviderError,
+    executor::{Executor, System},
+};
+
+/// Time a cancelled command gets to exit after SIGTERM before it is killed.
+const CANCEL_GRACE_PERIOD: Duration = Duration::from_secs(5);
+/// Time between the checks that a cancelled command is still running.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Unterminated output that waits for an answer: pacman's `[Y/n]` and `Enter a number (default=1):`,
+/// or the bare `==>` under paru's menus.
+const PROMPT: &str = r"(?i)(\[y/n\]:?|\(default=[^)]*\):?|^==>)\s*$";
+/// Time the output has to stop before an unterminated line is taken for a prompt.
+const PROMPT_IDLE: Duration = Duration::from_millis(200);
+const PASSWORD_PROMPT: &str = r"(?i)password( for [^:]*)?:\s*$";
+/// Lines of a pseudo-terminal kept to explain a failure, as its stderr can't be told apart.
+const PTY_TAIL_LINES: usize = 20;
+
+/// A program with its arguments, run without a shell so nothing in them is interpreted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cmd {
+    program: String,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+}
+
+impl Cmd {
+    pub fn new(program: &str) -> Self {
+        Cmd {
+            program: program.to_string(),
+            args: Vec::new(),
+            env: Vec::new(),
+        }
+    }
+
+    /// Runs `script` through the shell, only for what needs one (pipes, redirections).
+    #[cfg(target_os = "linux")]
+    pub fn shell(script: &str) -> Self {
+        Cmd::new("sh").args(["-c", script])
+    }
+
+    #[cfg(target_os = "windows")]
+    pub fn shell(script: &str) -> Self {
+        Cmd::new("powershell").args([
+            "-NoLogo",
+            "-NonInteractive",
+            "-NoProfile",
+            "-Command",
+            script,
+        ])
+    }
+
+    /// Runs `commands` one after the other, stopping at the first failure. Every argument is
+    /// quoted for the shell that chains them.
+    pub fn chain(commands: &[Cmd]) -> Self {
+        match commands {
+            [command] => command.clone(),
+            _ => Cmd::shell(
+                &commands
+                    .iter()
+                    .map(|command| command.to_string())
+                    .collect::<Vec<String>>()
+                    .join(CHAIN_SEPARATOR),
+            ),
+        }
+    }
+
+    pub fn arg(mut self, arg: &str) -> Self {
+        self.args.push(arg.to_string());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_string()));
+        self
+    }
+
+    /// Adds package names after checking they are identifiers, see [`identifier`].
+    pub fn packages<I, S>(self, packages: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let packages = packages
+            .into_iter()
+            .map(|package| identifier(package.as_ref()).map(String::from))
+            .collect::<Result<Vec<String>>>()?;
+        Ok(self.args(packages))
+    }
+
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Runs this command through `program`, e.g. `sudo`, keeping the environment.
+    pub fn wrap(self, program: &str, args: &[&str]) -> Self {
+        let mut wrapper = Cmd::new(program).args(args);
+        wrapper.args.push(self.program);
+        wrapper.args.extend(self.args);
+        wrapper.env = self.env;
+        wrapper
+    }
+
+    pub(crate) fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args)
+            .envs(self.env.iter().map(|(key, value)| (key, value)));
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+
+            cmd.creation_flags(0x08000000);
+        }
+        cmd
+    }
+
+    fn pty_command(&self) -> CommandBuilder {
+        let mut cmd = CommandBuilder::new(&self.program);
+        cmd.args(&self.args);
+        for (key, value) in &self.env {
+            cmd.env(key, value);
+        }
+        cmd
+    }
+}
+
+/// The command line as typed in the shell used by [`Cmd::shell`].
+impl fmt::Display for Cmd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in &self.env {
+            write!(f, "{}", env_assignment(key, value))?;
+        }
+        let words: Vec<String> = std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|word| quote(word))
+            .collect();
+        write!(f, "{}", words.join(" "))
+    }
+}
+
+#[cfg(target_os = "linux")]
+const CHAIN_SEPARATOR: &str = " && ";
+// Windows PowerShell has no `&&`
+#[cfg(target_os = "windows")]
+const CHAIN_SEPARATOR: &str = "; ";
+
+fn is_plain(word: &str) -> bool {
+    !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c))
+}
+
+#[cfg(target_os = "linux")]
+fn quote(word: &str) -> String {
+    if is_plain(word) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn quote(word: &str) -> String {
+    if is_plain(word) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "''"))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn env_assignment(key: &str, value: &str) -> String {
+    format!("{key}={} ", quote(value))
+}
+
+#[cfg(target_os = "windows")]
+fn env_assignment(key: &str, value: &str) -> String {
+    format!("$env:{key}={}; ", quote(value))
+}
+
+/// Checks a package name, id or ref before it is passed as an argument: it can't be empty,
+/// look like an option or contain whitespace and control characters.
+pub fn identifier(value: &str) -> Result<&str> {
+    if value.is_empty() {
+        return Err(anyhow!("Empty package name"));
+    }
+    if value.starts_with('-') {
+        return Err(anyhow!("Invalid package name {value:?}: starts with '-'"));
+    }
+    if value.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(anyhow!("Invalid package name {value:?}"));
+    }
+    Ok(value)
+}
+
+/// Like [`identifier`] for display names, which may contain spaces.
+pub fn name_argument(value: &str) -> Result<&str> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() || trimmed.starts_with('-') || value.chars().any(char::is_control) {
+        return Err(anyhow!("Invalid package name {value:?}"));
+    }
+    Ok(value)
+}
+
+#[cfg(target_os = "linux")]
+fn new_process_group(cmd: &mut Command) -> &mut Command {
+    use std::os::unix::process::CommandExt;
+
+    cmd.process_group(0)
+}
+
+#[cfg(target_os = "windows")]
+fn new_process_group(cmd: &mut Command) -> &mut Command {
+    use std::os::windows::process::CommandExt;
+
+    // CREATE_NO_WINDOW | CREATE_NEW_PROCESS_GROUP
+    cmd.creation_flags(0x08000000 | 0x00000200)
+}
+
+#[cfg(target_os = "linux")]
+fn kill_process_group(pid: u32) {
+    let group = format!("-{pid}");
+    let _ = Command::new("kill").args(["-TERM", "--", &group]).status();
+    let started = Instant::now();
+    while started.elapsed() < CANCEL_GRACE_PERIOD {
+        // Signal 0 only checks that a process of the group is left
+        let alive = Command::new("kill")
+            .args(["-0", "--", &group])
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if !alive {
+            return;
+        }
+        thread::sleep(CANCEL_POLL_INTERVAL);
+    }
+    let _ = Command::new("kill").args(["-KILL", "--", &group]).status();
+}
+
+#[cfg(target_os = "windows")]
+fn kill_process_group(pid: u32) {
+    use std::os::windows::process::CommandExt;
+
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .creation_flags(0x08000000)
+        .status();
+}
+
+/// Runs `command` on this machine. Providers go through their [`Executor`] instead.
+pub fn run(command: &Cmd) -> Result<String> {
+    Ok(System.run(command)?)
+}
+
+/// Like [`run`] for tools that exit with a non-zero code on success, e.g. `dnf check-update`.
+pub fn run_with_codes(command: &Cmd, success_codes: &[i32]) -> Result<String> {
+    Ok(System.run_with_codes(command, success_codes)?)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Success,
+    Cancelled,
+}
+
+/// Handle that stops a running [`CommandStream`] from another thread.
+#[derive(Debug, Clone)]
+pub struct Canceller {
+    /// `None` for replayed output, which has no process to stop.
+    pid: Option<u32>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Canceller {
+    /// Terminates the whole process group of the command, including `sudo` children.
+    pub fn cancel(&self) {
+        if self.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        if let Some(pid) = self.pid {
+            thread::spawn(move || kill_process_group(pid));
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// A line of output, tagged with the stream it was written to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    Stdout(String),
+    Stderr(String),
+    /// The command is waiting for an answer, see [`Responder`].
+    Prompt(String),
+    /// The line currently being rewritten with carriage returns, replaced by the next
+    /// `Progress` and finally by the `Stdout` line that ends it.
+    Progress(String),
+}
+
+impl Line {
+    pub fn text(&self) -> &str {
+        match self {
+            Line::Stdout(text) | Line::Stderr(text) | Line::Prompt(text) | Line::Progress(text) => {
+                text
+            }
+        }
+    }
+}
+
+/// Writes answers to the input of a running [`CommandStream`].
+#[derive(Clone)]
+pub struct Responder {
+    input: Arc<Mutex<Option<Box<dyn Write + Send>>>>,
+}
+
+impl Responder {
+    fn new(input: Option<Box<dyn Write + Send>>) -> Self {
+        Responder {
+            input: Arc::new(Mutex::new(input)),
+        }
+    }
+
+    pub fn answer(&self, answer: &str) -> Result<()> {
+        let mut input = self
+            .input
+            .lock()
+            .map_err(|_| anyhow!("Failed to write to the command"))?;
+        let input = input
+            .as_mut()
+            .context("The command does not accept input")?;
+        input.write_all(format!("{answer}\n").as_bytes())?;
+        input.flush()?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text())
+    }
+}
+
+/// Error returned when a command exits unsuccessfully.
+#[derive(Debug, Clone)]
+pub struct CommandFailed {
+    pub code: Option<i32>,
+    pub stderr: String,
+}
+
+impl fmt::Display for CommandFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "Command exited with code {code}"),
+            None => write!(f, "Command was terminated by a signal"),
+        }?;
+        if !self.stderr.is_empty() {
+            write!(f, "\n{}", self.stderr.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CommandFailed {}
+
+enum Process {
+    Pipe(Child),
+    Pty {
+        child: Box<dyn portable_pty::Child + Send + Sync>,
+        // the terminal is closed when the master side is dropped
+        _master: Box<dyn MasterPty + Send>,
+    },
+    Replay {
+        code: Option<i32>,
+    },
+}
+
+impl Process {
+    fn wait(&mut self) -> Result<(bool, Option<i32>)> {
+        match self {
+            Process::Pipe(child) => {
+                let status = child.wait()?;
+                Ok((status.success(), status.code()))
+            }
+            Process::Pty { child, .. } => {
+                let status = child.wait()?;
+                let code = status
+                    .signal()
+                    .is_none()
+                    .then_some(status.exit_code() as i32);
+                Ok((status.success(), code))
+            }
+            Process::Replay { code } => Ok((*code == Some(0), *code)),
+        }
+    }
+}
+
+pub struct CommandStream {
+    process: Process,
+    lines: Receiver<Line>,
+    readers: Vec<JoinHandle<()>>,
+    stderr: Arc<Mutex<Vec<String>>>,
+    canceller: Canceller,
+    responder: Responder,
+}
+impl CommandStream {
+    pub fn new(command: Cmd, stdin: Option<Vec<String>>) -> Result<Self> {
+        let mut child = new_process_group(&mut command.command())
+            .stderr(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|err| spawn_failed(&command, err.into()))?;
+
+        if let Some(inputs) = stdin {
+            let mut stdin = child.stdin.take().context("Failed to run command")?;
+            for input in inputs {
+                stdin.write_all(format!("{input}\r\n").as_bytes())?;
+            }
+        }
+
+        let stdout = child.stdout.take().context("Failed to run command")?;
+        let stderr = child.stderr.take().context("Failed to run command")?;
+        let responder = Responder::new(
+            child
+                .stdin
+                .take()
+                .map(|stdin| Box::new(stdin) as Box<dyn Write + Send>),
+        );
+
+        // Both pipes are drained concurrently so a chatty stderr can't block the child
+        let (sender, lines) = mpsc::channel();
+        let stderr_lines = Arc::new(Mutex::new(Vec::new()));
+        let readers = vec![
+            spawn_reader(stdout, sender.clone(), Line::Stdout, None),
+            spawn_reader(stderr, sender, Line::Stderr, Some(stderr_lines.clone())),
+        ];
+
+        let canceller = Canceller {
+            pid: Some(child.id()),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        };
+
+        Ok(CommandStream {
+            process: Process::Pipe(child),
+            lines,
+            readers,
+            stderr: stderr_lines,
+            canceller,
+            responder,
+        })
+    }
+
+    /// Runs the command under a pseudo-terminal so it can ask questions, which are reported as
+    /// [`Line::Prompt`]. The `passwords` are only written when the command asks for a password,
+    /// after the terminal has disabled echo. stdout and stderr are merged, the last lines are
+    /// reported as the stderr of [`CommandFailed`].
+    pub fn new_pty(command: Cmd, passwords: Option<Vec<String>>) -> Result<Self> {
+        let pair = native_pty_system().openpty(PtySize {
+            rows: 40,
+            cols: 200,
+            pixel_width: 0,
+            pixel_height: 0,
+        })?;
+        let mut cmd = command.pty_command();
+        cmd.cwd(std::env::current_dir()?);
+        let child = pair
+            .slave
+            .spawn_command(cmd)
+            .map_err(|err| spawn_failed(&command, err))?;
+        drop(pair.slave);
+
+        let pid = child.process_id().context("Failed to run command")?;
+        let reader = pair.master.try_clone_reader()?;
+        let responder = Responder::new(Some(pair.master.take_writer()?));
+
+        let (sender, lines) = mpsc::channel();
+        let tail = Arc::new(Mutex::new(Vec::new()));
+        let readers = vec![spawn_pty_reader(
+            reader,
+            sender,
+            responder.clone(),
+            passwords.unwrap_or_default(),
+            tail.clone(),
+        )];
+
+        Ok(CommandStream {
+            process: Process::Pty {
+                child,
+                _master: pair.master,
+            },
+            lines,
+            readers,
+            stderr: tail,
+            canceller: Canceller {
+                pid: Some(pid),
+                cancelled: Arc::new(AtomicBool::new(false)),
+            },
+            responder,
+        })
+    }
+
+    /// Streams `lines` as if a command had written them, then exits with `code`.
+    pub fn replay(lines: Vec<Line>, code: Option<i32>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let mut stderr = Vec::new();
+        for line in lines {
+            if let Line::Stderr(text) = &line {
+                stderr.push(text.clone());
+            }
+            let _ = sender.send(line);
+        }
+        CommandStream {
+            process: Process::Replay { code },
+            lines: receiver,
+            readers: Vec::new(),
+            stderr: Arc::new(Mutex::new(stderr)),
+            canceller: Canceller {
+                pid: None,
+                cancelled: Arc::new(AtomicBool::new(false)),
+            },
+            responder: Responder::new(None),
+        }
+    }
+
+    pub fn responder(&self) -> Responder {
+        self.responder.clone()
+    }
+
+    pub fn canceller(&self) -> Canceller {
+        self.canceller.clone()
+    }
+
+    pub fn cancel(&self) {
+        self.canceller.cancel()
+    }
+
+    /// Waits for the command to exit. Failures are reported as [`CommandFailed`].
+    pub fn close(&mut self) -> Result<Outcome> {
+        let (success, code) = self.process.wait()?;
+        for reader in self.readers.drain(..) {
+            let _ = reader.join();
+        }
+        if self.canceller.is_cancelled() {
+            Ok(Outcome::Cancelled)
+        } else if success {
+            Ok(Outcome::Success)
+        } else {
+            let stderr = self
+                .stderr
+                .lock()
+                .map(|lines| lines.join("\n"))
+                .unwrap_or_default();
+            Err(CommandFailed { code, stderr }.into())
+        }
+    }
+}
+
+/// Reports a program that isn't installed as [`ProviderError::Unavailable`].
+pub(crate) fn spawn_failed(command: &Cmd, err: anyhow::Error) -> anyhow::Error {
+    let missing = err.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|err| err.kind() == io::ErrorKind::NotFound)
+    });
+    if missing {
+        ProviderError::Unavailable(command.program.clone()).into()
+    } else {
+        err
+    }
+}
+
+fn spawn_reader<R: Read + Send + 'static>(
+    pipe: R,
+    sender: Sender<Line>,
+    tag: fn(String) -> Line,
+    collect: Option<Arc<Mutex<Vec<String>>>>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(pipe).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            // Only the final state of a line rewritten with carriage returns is kept
+            let line = line
+                .trim_end_matches('\r')
+                .rsplit('\r')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            if let Some(collect) = &collect {
+                if let Ok(mut lines) = collect.lock() {
+                    lines.push(line.clone());
+                }
+            }
+            let _ = sender.send(tag(line));
+        }
+    })
+}
+
+fn spawn_pty_reader(
+    mut pty: Box<dyn Read + Send>,
+    sender: Sender<Line>,
+    responder: Responder,
+    mut passwords: Vec<String>,
+    tail: Arc<Mutex<Vec<String>>>,
+) -> JoinHandle<()> {
+    let (chunks, received) = mpsc::channel::<Vec<u8>>();
+    thread::spawn(move || {
+        let mut buffer = [0u8; 4096];
+        loop {
+            match pty.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => {
+                    if chunks.send(buffer[..read].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    thread::spawn(move || {
+        let mut output = PtyLines::new();
+        loop {
+            match received.recv_timeout(PROMPT_IDLE) {
+                Ok(chunk) => {
+                    for line in output.push(&chunk) {
+                        if let (Line::Stdout(text), Ok(mut tail)) = (&line, tail.lock()) {
+                            if tail.len() == PTY_TAIL_LINES {
+                                tail.remove(0);
+                            }
+                            tail.push(text.clone());
+                        }
+                        let _ = sender.send(line);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => match output.idle() {
+                    Some(prompt) if !passwords.is_empty() && output.is_password(&prompt) => {
+                        let _ = sender.send(Line::Stdout(prompt));
+                        let _ = responder.answer(&passwords.remove(0));
+                    }
+                    Some(prompt) => {
+                        let _ = sender.send(Line::Prompt(prompt.trim().to_string()));
+                    }
+                    None => {}
+                },
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        if let Some(line) = output.finish() {
+            let _ = sender.send(line);
+        }
+    })
+}
+
+/// Splits the output of a pseudo-terminal into lines. The unterminated rest is only taken for a
+/// prompt by [`PtyLines::idle`], as the terminal can also deliver a line in pieces.
+struct PtyLines {
+    prompt: Regex,
+    password_prompt: Regex,
+    pending: Vec<u8>,
+}
+
+impl PtyLines {
+    fn new() -> Self {
+        PtyLines {
+            prompt: Regex::new(PROMPT).expect("Invalid regex"),
+            password_prompt: Regex::new(PASSWORD_PROMPT).expect("Invalid regex"),
+            pending: Vec::new(),
+        }
+    }
+
+    /// The lines completed by `chunk`, then the progress it rewrote.
+    fn push(&mut self, chunk: &[u8]) -> Vec<Line> {
+        self.pending.extend_from_slice(chunk);
+        let mut lines = Vec::new();
+
+        while let Some(index) = self.pending.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=index).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\r', '\n']);
+            // Keep what is left after progress bars rewrote the line
+            let line = line.rsplit('\r').next().unwrap_or_default();
+            lines.push(Line::Stdout(line.to_string()));
+        }
+
+        // A trailing '\r' may still be followed by '\n'
+        let rewrite = self.pending[..self.pending.len().saturating_sub(1)]
+            .iter()
+            .rposition(|byte| *byte == b'\r');
+        if let Some(index) = rewrite {
+            self.pending.drain(..=index);
+            lines.push(Line::Progress(
+                String::from_utf8_lossy(&self.pending).to_string(),
+            ));
+        }
+        lines
+    }
+
+    /// Takes the unterminated line once the output stopped, if it waits for an answer.
+    fn idle(&mut self) -> Option<String> {
+        let partial = String::from_utf8_lossy(&self.pending).to_string();
+        let text = ansi::strip(&partial);
+        if !self.prompt.is_match(&text) && !self.password_prompt.is_match(&text) {
+            return None;
+        }
+        self.pending.clear();
+        Some(partial)
+    }
+
+    fn is_password(&self, prompt: &str) -> bool {
+        self.password_prompt.is_match(&ansi::strip(prompt))
+    }
+
+    /// The unterminated line left when the output ended.
+    fn finish(self) -> Option<Line> {
+        (!self.pending.is_empty())
+            .then(|| Line::Stdout(String::from_utf8_lossy(&self.pending).to_string()))
+    }
+}
+
+impl Iterator for CommandStream {
+    type Item = Line;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lines.recv().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn killing_a_group_stops_once_it_has_exited() {
+        let mut child = new_process_group(Command::new("sleep").arg("30"))
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        let reaper = thread::spawn(move || child.wait());
+
+        let started = Instant::now();
+        kill_process_group(pid);
+        assert!(started.elapsed() < CANCEL_GRACE_PERIOD);
+        assert!(!reaper.join().unwrap().unwrap().success());
+    }
+
+    #[test]
+    fn prompt_split_across_chunks_waits_for_the_output_to_stop() {
+        let mut output = PtyLines::new();
+        assert_eq!(output.push(b":: Proceed with installation? [Y/"), []);
+        assert_eq!(output.idle(), None);
+        assert_eq!(output.push(b"n] "), []);
+        assert_eq!(
+            output.idle().as_deref(),
+            Some(":: Proceed with installation? [Y/n] ")
+        );
+        assert!(output.finish().is_none());
+    }
+
+    #[test]
+    fn line_split_after_a_question_mark_is_not_a_prompt() {
+        let mut output = PtyLines::new();
+        assert_eq!(output.push(b":: Import PGP key 4AEE18F8, \"Arch?"), []);
+        assert_eq!(output.idle(), None);
+        assert_eq!(
+            output.push(b" Developer\"?\r\n"),
+            [Line::Stdout(
+                ":: Import PGP key 4AEE18F8, \"Arch? Developer\"?".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn paru_menu_prompt_is_only_the_bare_arrow() {
+        let mut output = PtyLines::new();
+        assert_eq!(output.push(b"\x1b[1;32m==> Making pack"), []);
+        assert_eq!(output.idle(), None);
+        assert_eq!(
+            output.push(b"age: foo\n\x1b[1;32m==>\x1b[0m "),
+            [Line::Stdout(
+                "\x1b[1;32m==> Making package: foo".to_string()
+            )]
+        );
+        assert_eq!(output.idle().as_deref(), Some("\x1b[1;32m==>\x1b[0m "));
+    }
+
+    #[test]
+    fn password_prompt_is_recognised_once_idle() {
+        let mut output = PtyLines::new();
+        assert_eq!(output.push(b"[sudo] password for "), []);
+        assert_eq!(output.idle(), None);
+        output.push(b"user: ");
+        let prompt = output.idle().unwrap();
+        assert!(output.is_password(&prompt));
+    }
+}
@@ -0,0 +1,112 @@
+//! What a provider knows about a package, laid out for a details pane instead of raw output.
+
+/// Fields holding the packages a package needs, as pacman and paru name them.
+const DEPENDENCY_FIELDS: [&str; 1] = ["Depends On"];
+const OPTIONAL_DEPENDENCY_FIELDS: [&str; 1] = ["Optional Deps"];
+const RELEASE_NOTES_FIELDS: [&str; 1] = ["Release Notes"];
+/// How pacman and paru write an empty field.
+const NONE: &str = "None";
+
+/// The details of a package, with the output they were read from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackageDetails {
+    /// Label and value of the remaining fields, in the order the provider printed them.
+    pub fields: Vec<(String, String)>,
+    /// Packages it needs, possibly with a version constraint, e.g. `glibc>=2.39`.
+    pub dependencies: Vec<String>,
+    /// Packages it can use, with what for when the provider tells.
+    pub optional_dependencies: Vec<(String, String)>,
+    /// Label and address of each web page about it.
+    pub links: Vec<(String, String)>,
+    pub release_notes: Option<String>,
+    /// The output of the provider, for what the fields leave out.
+    pub raw: String,
+}
+
+impl PackageDetails {
+    /// Sorts `Label: value` fields into dependencies, links and release notes, leaving the rest
+    /// as fields.
+    pub fn from_fields(raw: String, fields: Vec<(String, String)>) -> Self {
+        let mut details = PackageDetails {
+            raw,
+            ..Default::default()
+        };
+        for (label, value) in fields {
+            if DEPENDENCY_FIELDS.contains(&label.as_str()) {
+                details.dependencies.extend(
+                    value
+                        .split_whitespace()
+                        .filter(|name| *name != NONE)
+                        .map(str::to_string),
+                );
+            } else if OPTIONAL_DEPENDENCY_FIELDS.contains(&label.as_str()) {
+                details.optional_dependencies.extend(
+                    value
+                        .lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty() && *line != NONE)
+                        .map(|line| match line.split_once(':') {
+                            Some((name, reason)) => {
+                                (name.trim().to_string(), reason.trim().to_string())
+                            }
+                            None => (line.to_string(), String::new()),
+                        }),
+                );
+            } else if RELEASE_NOTES_FIELDS.contains(&label.as_str()) {
+                details.release_notes = Some(value).filter(|notes| !notes.is_empty());
+            } else if is_url(&value) {
+                details.links.push((label, value));
+            } else {
+                details.fields.push((label, value));
+            }
+        }
+        details
+    }
+}
+
+/// The `Label : value` fields of `text`, as printed by `pacman -Si`, `dnf info` or `winget show`.
+/// Indented lines continue the field above them, lines without a label before the first field
+/// are skipped and a blank line after it ends the fields, e.g. before the next repository.
+pub fn parse_fields(text: &str) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !fields.is_empty() {
+                break;
+            }
+            continue;
+        }
+        let indented = line.starts_with(char::is_whitespace);
+        match (indented, fields.last_mut()) {
+            // dnf starts the continuation lines of a field with its colon
+            (true, Some((_, value))) => {
+                let line = line.trim();
+                let line = line.strip_prefix(':').map_or(line, str::trim_start);
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(line);
+            }
+            _ => {
+                if let Some((label, value)) = line.split_once(':') {
+                    fields.push((label.trim().to_string(), value.trim().to_string()));
+                }
+            }
+        }
+    }
+    fields
+}
+
+/// The name a dependency is known by, without its version constraint or description.
+pub fn dependency_name(dependency: &str) -> &str {
+    dependency
+        .split(['<', '>', '=', ':'])
+        .next()
+        .unwrap_or(dependency)
+        .trim()
+}
+
+fn is_url(value: &str) -> bool {
+    (value.starts_with("https://") || value.starts_with("http://"))
+        && !value.contains(char::is_whitespace)
+}
@@ -0,0 +1,228 @@
+use std::{fmt, io, sync::OnceLock};
+
+use regex::RegexSet;
+
+use crate::command::CommandFailed;
+
+pub type Result<T, E = ProviderError> = std::result::Result<T, E>;
+
+/// Exit code of a shell that could not find the program.
+const COMMAND_NOT_FOUND: i32 = 127;
+
+/// Output of pacman, dnf and sudo when another process holds the lock.
+const LOCKED: [&str; 5] = [
+    r"unable to lock database",
+    r"could not lock database",
+    r"waiting for process with pid",
+    r"failed to obtain the transaction lock",
+    r"another app is currently holding the .* lock",
+];
+const AUTH_FAILED: [&str; 5] = [
+    r"sorry, try again",
+    r"incorrect password attempts?",
+    r"authentication failure",
+    r"not authorized",
+    r"a password is required",
+];
+/// pkexec when the polkit dialog is closed.
+const DISMISSED: [&str; 1] = [r"request dismissed"];
+const NOT_FOUND: [&str; 7] = [
+    r"target not found",
+    r"no match for argument",
+    r"nothing matches",
+    r"no package found matching",
+    r"error: .* not installed",
+    r"unable to find package",
+    r"no matching packages",
+];
+const NETWORK: [&str; 8] = [
+    r"could not resolve host",
+    r"temporary failure in name resolution",
+    r"network is unreachable",
+    r"connection (timed out|refused)",
+    r"failed (retrieving|to retrieve) (some )?files?",
+    r"cannot download",
+    r"curl error",
+    r"unable to connect",
+];
+
+/// Why a provider failed, so each interface can explain it and offer a way out.
+#[derive(Debug)]
+pub enum ProviderError {
+    /// The program of the provider isn't installed or can't be started.
+    Unavailable(String),
+    /// The password was rejected or the user isn't allowed to run the command.
+    AuthFailed,
+    /// A download or a request to a remote service failed.
+    Network(String),
+    /// The provider printed something it couldn't understand.
+    Parse {
+        line: String,
+        reason: String,
+    },
+    /// The command exited unsuccessfully for another reason.
+    CommandFailed {
+        code: Option<i32>,
+        stderr: String,
+    },
+    /// Another package manager holds the lock of the package database.
+    LockedDatabase(String),
+    /// The provider doesn't know the package.
+    PackageNotFound(String),
+    /// The user dismissed the authorisation or stopped the command.
+    Cancelled,
+    Other(anyhow::Error),
+}
+
+impl ProviderError {
+    /// A [`ProviderError::Parse`] pointing at the line of `text` that `err` failed on.
+    pub fn json(text: &str, err: serde_json::Error) -> Self {
+        let line = text
+            .lines()
+            .nth(err.line().saturating_sub(1))
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        ProviderError::Parse {
+            line,
+            reason: err.to_string(),
+        }
+    }
+
+    /// What the user can do about it, `None` when there is nothing obvious.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            ProviderError::Unavailable(_) => {
+                Some("Install it or make sure it is in the PATH, then try again.")
+            }
+            ProviderError::AuthFailed => Some("Check the password and try again."),
+            ProviderError::Network(_) => Some("Check the internet connection and try again."),
+            ProviderError::Parse { .. } => {
+                Some("This version of the package manager may not be supported yet.")
+            }
+            ProviderError::LockedDatabase(_) => {
+                Some("Wait for the other package manager to finish, then try again.")
+            }
+            ProviderError::PackageNotFound(_) => {
+                Some("The package list may be outdated, reload it and try again.")
+            }
+            ProviderError::CommandFailed { .. }
+            | ProviderError::Cancelled
+            | ProviderError::Other(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderError::Unavailable(program) => write!(f, "{program} is not available"),
+            ProviderError::AuthFailed => write!(f, "Authentication failed"),
+            ProviderError::Network(message) => write!(f, "Network error: {message}"),
+            ProviderError::Parse { line, reason } => {
+                write!(f, "Unexpected output ({reason}): {line}")
+            }
+            ProviderError::CommandFailed { code, stderr } => write!(
+                f,
+                "{}",
+                CommandFailed {
+                    code: *code,
+                    stderr: stderr.clone()
+                }
+            ),
+            ProviderError::LockedDatabase(message) => {
+                write!(f, "The package database is locked\n{message}")
+            }
+            ProviderError::PackageNotFound(message) => write!(f, "Package not found\n{message}"),
+            ProviderError::Cancelled => write!(f, "Cancelled"),
+            ProviderError::Other(err) => write!(f, "{err:#}"),
+        }
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+/// Tells the failures apart by what the command wrote.
+impl From<CommandFailed> for ProviderError {
+    fn from(failed: CommandFailed) -> Self {
+        static PATTERNS: OnceLock<[RegexSet; 5]> = OnceLock::new();
+        let [locked, auth_failed, dismissed, not_found, network] = PATTERNS.get_or_init(|| {
+            let set = |patterns: &[&str]| {
+                RegexSet::new(patterns.iter().map(|pattern| format!("(?i){pattern}")))
+                    .expect("Invalid regex")
+            };
+            [
+                set(&LOCKED),
+                set(&AUTH_FAILED),
+                set(&DISMISSED),
+                set(&NOT_FOUND),
+                set(&NETWORK),
+            ]
+        });
+        let stderr = failed.stderr.trim().to_string();
+        if failed.code == Some(COMMAND_NOT_FOUND) {
+            // e.g. `sh: 1: paru: not found` or `bash: paru: command not found`
+            let program = stderr.rsplit(':').nth(1).map_or(&*stderr, str::trim);
+            ProviderError::Unavailable(program.to_string())
+        } else if dismissed.is_match(&stderr) {
+            ProviderError::Cancelled
+        } else if locked.is_match(&stderr) {
+            ProviderError::LockedDatabase(stderr)
+        } else if auth_failed.is_match(&stderr) {
+            ProviderError::AuthFailed
+        } else if not_found.is_match(&stderr) {
+            ProviderError::PackageNotFound(stderr)
+        } else if network.is_match(&stderr) {
+            ProviderError::Network(stderr)
+        } else {
+            ProviderError::CommandFailed {
+                code: failed.code,
+                stderr: failed.stderr,
+            }
+        }
+    }
+}
+
+impl From<anyhow::Error> for ProviderError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<ProviderError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        let err = match err.downcast::<CommandFailed>() {
+            Ok(failed) => return failed.into(),
+            Err(err) => err,
+        };
+        if err
+            .chain()
+            .any(|cause| cause.downcast_ref::<reqwest::Error>().is_some())
+        {
+            return ProviderError::Network(format!("{err:#}"));
+        }
+        ProviderError::Other(err)
+    }
+}
+
+impl From<io::Error> for ProviderError {
+    fn from(err: io::Error) -> Self {
+        ProviderError::Other(err.into())
+    }
+}
+
+impl From<serde_json::Error> for ProviderError {
+    fn from(err: serde_json::Error) -> Self {
+        ProviderError::Other(err.into())
+    }
+}
+
+impl From<alpm::Error> for ProviderError {
+    fn from(err: alpm::Error) -> Self {
+        ProviderError::Other(err.into())
+    }
+}
+
+impl From<reqwest::Error> for ProviderError {
+    fn from(err: reqwest::Error) -> Self {
+        ProviderError::Network(err.to_string())
+    }
+}
@@ -0,0 +1,307 @@
+use std::{
+    fmt, fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api,
+    command::{self, Cmd, CommandFailed, CommandStream, Line},
+    error::Result,
+};
+
+/// Runs the commands of the providers. [`System`] runs them for real, [`Replay`] answers with
+/// recorded output so providers can be tested without the tools installed.
+pub trait Executor: fmt::Debug + Send + Sync {
+    /// Runs `command` to completion, whatever its exit code.
+    fn output(&self, command: &Cmd) -> Result<Output>;
+
+    /// Starts `command` with its output read through pipes, see [`CommandStream::new`].
+    fn stream(&self, command: Cmd, stdin: Option<Vec<String>>) -> Result<CommandStream>;
+
+    /// Starts `command` under a pseudo-terminal, see [`CommandStream::new_pty`].
+    fn stream_pty(&self, command: Cmd, passwords: Option<Vec<String>>) -> Result<CommandStream>;
+
+    /// Downloads `url`, failing when the server doesn't answer with a success.
+    fn fetch(&self, url: &str) -> Result<Vec<u8>>;
+
+    fn run(&self, command: &Cmd) -> Result<String> {
+        self.run_with_codes(command, &[0])
+    }
+
+    /// Like [`Executor::run`] for tools that exit with a non-zero code on success.
+    fn run_with_codes(&self, command: &Cmd, success_codes: &[i32]) -> Result<String> {
+        self.output(command)?.success(success_codes)
+    }
+}
+
+/// What a finished command wrote and how it exited.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Output {
+    /// `None` when the command was terminated by a signal.
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Output {
+    /// The stdout when the command exited with one of `success_codes`, otherwise the failure told
+    /// apart by its stderr, see [`ProviderError`](crate::error::ProviderError).
+    pub fn success(self, success_codes: &[i32]) -> Result<String> {
+        if self.code.is_some_and(|code| success_codes.contains(&code)) {
+            Ok(self.stdout)
+        } else {
+            Err(CommandFailed {
+                code: self.code,
+                stderr: self.stderr,
+            }
+            .into())
+        }
+    }
+}
+
+/// Shared by a provider and its clones. Each provider takes one in `with_executor`, so its
+/// commands can run through a [`Replay`] instead of [`system`].
+pub type SharedExecutor = Arc<dyn Executor>;
+
+pub fn system() -> SharedExecutor {
+    Arc::new(System)
+}
+
+/// Runs the commands on this machine.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct System;
+
+impl Executor for System {
+    fn output(&self, command: &Cmd) -> Result<Output> {
+        let output = command
+            .command()
+            .output()
+            .map_err(|err| command::spawn_failed(command, err.into()))?;
+        Ok(Output {
+            code: output.status.code(),
+            stdout: String::from_utf8(output.stdout).map_err(anyhow::Error::from)?,
+            stderr: String::from_utf8(output.stderr).map_err(anyhow::Error::from)?,
+        })
+    }
+
+    fn stream(&self, command: Cmd, stdin: Option<Vec<String>>) -> Result<CommandStream> {
+        Ok(CommandStream::new(command, stdin)?)
+    }
+
+    fn stream_pty(&self, command: Cmd, passwords: Option<Vec<String>>) -> Result<CommandStream> {
+        Ok(CommandStream::new_pty(command, passwords)?)
+    }
+
+    fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        Ok(api::download(url)?)
+    }
+}
+
+/// The output of a command line, as shown by the `Display` of [`Cmd`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    pub command: String,
+    #[serde(flatten)]
+    pub output: Output,
+}
+
+/// Answers each command with the output recorded for its command line, failing for commands
+/// that were not recorded. Every command asked for is kept, see [`Replay::commands`]. Downloads
+/// are answered the same way by URL.
+#[derive(Debug, Default)]
+pub struct Replay {
+    records: Vec<Record>,
+    downloads: Vec<(String, Vec<u8>)>,
+    commands: Mutex<Vec<String>>,
+}
+
+impl Replay {
+    pub fn new() -> Self {
+        Replay::default()
+    }
+
+    /// Loads records saved by [`Recorder::save`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let records = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Ok(Replay {
+            records,
+            ..Default::default()
+        })
+    }
+
+    /// `command` succeeds, writing `stdout`.
+    pub fn record(self, command: &str, stdout: &str) -> Self {
+        self.record_output(
+            command,
+            Output {
+                code: Some(0),
+                stdout: stdout.to_string(),
+                stderr: String::new(),
+            },
+        )
+    }
+
+    pub fn record_output(mut self, command: &str, output: Output) -> Self {
+        self.records.push(Record {
+            command: command.to_string(),
+            output,
+        });
+        self
+    }
+
+    /// Downloading `url` answers `body`.
+    pub fn record_download(mut self, url: &str, body: impl Into<Vec<u8>>) -> Self {
+        self.downloads.push((url.to_string(), body.into()));
+        self
+    }
+
+    /// The command lines asked for so far, in order.
+    pub fn commands(&self) -> Vec<String> {
+        self.commands
+            .lock()
+            .map(|commands| commands.clone())
+            .unwrap_or_default()
+    }
+
+    fn find(&self, command: &Cmd) -> Result<Output> {
+        let command = command.to_string();
+        if let Ok(mut commands) = self.commands.lock() {
+            commands.push(command.clone());
+        }
+        self.records
+            .iter()
+            .find(|record| record.command == command)
+            .map(|record| record.output.clone())
+            .ok_or_else(|| anyhow!("No output recorded for `{command}`").into())
+    }
+
+    fn replay(&self, command: &Cmd) -> Result<CommandStream> {
+        let output = self.find(command)?;
+        let lines = output
+            .stdout
+            .lines()
+            .map(|line| Line::Stdout(line.to_string()))
+            .chain(
+                output
+                    .stderr
+                    .lines()
+                    .map(|line| Line::Stderr(line.to_string())),
+            )
+            .collect();
+        Ok(CommandStream::replay(lines, output.code))
+    }
+}
+
+impl Executor for Replay {
+    fn output(&self, command: &Cmd) -> Result<Output> {
+        self.find(command)
+    }
+
+    fn stream(&self, command: Cmd, _: Option<Vec<String>>) -> Result<CommandStream> {
+        self.replay(&command)
+    }
+
+    fn stream_pty(&self, command: Cmd, _: Option<Vec<String>>) -> Result<CommandStream> {
+        self.replay(&command)
+    }
+
+    fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        self.downloads
+            .iter()
+            .find(|(recorded, _)| recorded == url)
+            .map(|(_, body)| body.clone())
+            .ok_or_else(|| anyhow!("No download recorded for {url}").into())
+    }
+}
+
+/// Runs the commands through another executor and keeps their output, to capture fixtures
+/// for [`Replay`]. Streams are read to the end before they are returned, so prompts can't be
+/// answered while recording. Downloads are passed through without being kept.
+#[derive(Debug)]
+pub struct Recorder {
+    executor: SharedExecutor,
+    records: Mutex<Vec<Record>>,
+}
+
+impl Recorder {
+    pub fn new(executor: SharedExecutor) -> Self {
+        Recorder {
+            executor,
+            records: Mutex::default(),
+        }
+    }
+
+    pub fn records(&self) -> Vec<Record> {
+        self.records
+            .lock()
+            .map(|records| records.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(&self.records())?)?;
+        Ok(())
+    }
+
+    fn keep(&self, command: &Cmd, output: Output) {
+        if let Ok(mut records) = self.records.lock() {
+            records.push(Record {
+                command: command.to_string(),
+                output,
+            });
+        }
+    }
+
+    fn record_stream(&self, command: &Cmd, mut stream: CommandStream) -> Result<CommandStream> {
+        let lines: Vec<Line> = stream.by_ref().collect();
+        let code = match stream.close() {
+            Ok(_) => Some(0),
+            Err(err) => match err.downcast_ref::<CommandFailed>() {
+                Some(failed) => failed.code,
+                None => return Err(err.into()),
+            },
+        };
+        let text = |stderr: bool| {
+            lines
+                .iter()
+                .filter(|line| matches!(line, Line::Stderr(_)) == stderr)
+                .map(|line| format!("{line}\n"))
+                .collect::<String>()
+        };
+        self.keep(
+            command,
+            Output {
+                code,
+                stdout: text(false),
+                stderr: text(true),
+            },
+        );
+        Ok(CommandStream::replay(lines, code))
+    }
+}
+
+impl Executor for Recorder {
+    fn output(&self, command: &Cmd) -> Result<Output> {
+        let output = self.executor.output(command)?;
+        self.keep(command, output.clone());
+        Ok(output)
+    }
+
+    fn stream(&self, command: Cmd, stdin: Option<Vec<String>>) -> Result<CommandStream> {
+        let stream = self.executor.stream(command.clone(), stdin)?;
+        self.record_stream(&command, stream)
+    }
+
+    fn stream_pty(&self, command: Cmd, passwords: Option<Vec<String>>) -> Result<CommandStream> {
+        let stream = self.executor.stream_pty(command.clone(), passwords)?;
+        self.record_stream(&command, stream)
+    }
+
+    fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        self.executor.fetch(url)
+    }
+}
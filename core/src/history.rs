@@ -0,0 +1,302 @@
+use std::{
+    path::Path,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use strum_macros::{Display, EnumString};
+
+use crate::{
+    package::PackageData,
+    provider::{Change, Operation},
+    utils,
+};
+
+const SCHEMA: &str = "
+    PRAGMA foreign_keys = ON;
+    CREATE TABLE IF NOT EXISTS transactions (
+        id INTEGER PRIMARY KEY,
+        provider TEXT NOT NULL,
+        started INTEGER NOT NULL,
+        finished INTEGER,
+        status TEXT NOT NULL,
+        exit_code INTEGER,
+        transcript TEXT NOT NULL DEFAULT ''
+    );
+    CREATE TABLE IF NOT EXISTS transaction_packages (
+        transaction_id INTEGER NOT NULL REFERENCES transactions (id) ON DELETE CASCADE,
+        package TEXT NOT NULL,
+        action TEXT NOT NULL,
+        version_before TEXT,
+        version_after TEXT
+    );
+    CREATE INDEX IF NOT EXISTS transaction_packages_transaction
+        ON transaction_packages (transaction_id);
+";
+
+/// What a transaction did to a package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum Action {
+    Install,
+    Remove,
+    Update,
+    Downgrade,
+}
+
+impl From<Operation> for Action {
+    fn from(operation: Operation) -> Self {
+        match operation {
+            Operation::Install => Action::Install,
+            Operation::Remove => Action::Remove,
+        }
+    }
+}
+
+/// How a transaction ended, `Running` until it does or when the app quit first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum Status {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+/// A package of a transaction, with its installed version before and after it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Qualified name, as the provider takes it.
+    pub package: String,
+    pub action: Action,
+    pub version_before: Option<String>,
+    /// Unset until the provider reloads after the transaction.
+    pub version_after: Option<String>,
+}
+
+impl Entry {
+    pub fn new(package: &str, action: Action, version_before: Option<String>) -> Self {
+        Entry {
+            package: package.to_string(),
+            action,
+            version_before,
+            version_after: None,
+        }
+    }
+}
+
+/// A command started from the window, as kept in the [`History`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    pub id: i64,
+    pub provider: String,
+    pub started: SystemTime,
+    pub finished: Option<SystemTime>,
+    pub status: Status,
+    pub exit_code: Option<i32>,
+    pub entries: Vec<Entry>,
+}
+
+/// What reverts a transaction.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Undo {
+    /// Removes what was installed and installs again what was removed.
+    pub changes: Vec<Change>,
+    /// Package and version to go back to, for the providers that can install older versions.
+    pub downgrades: Vec<(String, String)>,
+}
+
+impl Undo {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.downgrades.is_empty()
+    }
+}
+
+impl Transaction {
+    /// What reverts the packages this transaction changed, going by their versions.
+    pub fn undo(&self) -> Undo {
+        let mut undo = Undo::default();
+        for entry in &self.entries {
+            let package = entry.package.clone();
+            match (&entry.version_before, &entry.version_after) {
+                (None, Some(_)) => undo.changes.push(Change {
+                    operation: Operation::Remove,
+                    package,
+                }),
+                (Some(_), None) if entry.action == Action::Remove => undo.changes.push(Change {
+                    operation: Operation::Install,
+                    package,
+                }),
+                (Some(before), Some(after)) if before != after => {
+                    undo.downgrades.push((package, before.clone()))
+                }
+                // Unchanged, or the provider didn't reload since
+                _ => {}
+            }
+        }
+        undo
+    }
+}
+
+/// A journal of the transactions started from the window, so they can be looked back at and
+/// undone.
+pub struct History {
+    connection: Connection,
+}
+
+impl History {
+    /// Opens `history.db` in [`utils::system_path`].
+    pub fn open() -> Result<Self> {
+        let mut path = utils::system_path()?;
+        path.push("history.db");
+        History::open_at(path)
+    }
+
+    pub fn open_at(path: impl AsRef<Path>) -> Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(History { connection })
+    }
+
+    /// Records that a transaction of `provider` started, returning its id.
+    pub fn begin(&mut self, provider: &str, entries: &[Entry]) -> Result<i64> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO transactions (provider, started, status) VALUES (?1, ?2, ?3)",
+            params![
+                provider,
+                to_seconds(SystemTime::now()),
+                Status::Running.to_string()
+            ],
+        )?;
+        let id = transaction.last_insert_rowid();
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO transaction_packages
+                    (transaction_id, package, action, version_before, version_after)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for entry in entries {
+                insert.execute(params![
+                    id,
+                    entry.package,
+                    entry.action.to_string(),
+                    entry.version_before,
+                    entry.version_after,
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(id)
+    }
+
+    /// Records how the transaction ended and what its commands printed.
+    pub fn finish(
+        &self,
+        id: i64,
+        status: Status,
+        exit_code: Option<i32>,
+        transcript: &str,
+    ) -> Result<()> {
+        self.connection.execute(
+            "UPDATE transactions SET finished = ?2, status = ?3, exit_code = ?4, transcript = ?5
+            WHERE id = ?1",
+            params![
+                id,
+                to_seconds(SystemTime::now()),
+                status.to_string(),
+                exit_code,
+                transcript
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Records the installed versions of the packages of the transaction, as `packages`
+    /// list them once the provider reloaded.
+    pub fn record_versions_after(&mut self, id: i64, packages: &[PackageData]) -> Result<()> {
+        let transaction = self.connection.transaction()?;
+        let names: Vec<String> = {
+            let mut select = transaction
+                .prepare("SELECT package FROM transaction_packages WHERE transaction_id = ?1")?;
+            let names = select
+                .query_map([id], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()?;
+            names
+        };
+        for name in names {
+            let version = packages
+                .iter()
+                .find(|package| package.qualified_name == name)
+                .and_then(|package| package.installed_version.clone());
+            transaction.execute(
+                "UPDATE transaction_packages SET version_after = ?3
+                WHERE transaction_id = ?1 AND package = ?2",
+                params![id, name, version],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// The last `limit` transactions, the latest first.
+    pub fn transactions(&self, limit: usize) -> Result<Vec<Transaction>> {
+        let mut select = self.connection.prepare(
+            "SELECT id, provider, started, finished, status, exit_code
+            FROM transactions ORDER BY id DESC LIMIT ?1",
+        )?;
+        let mut transactions = select
+            .query_map([limit as i64], |row| {
+                let status: String = row.get(4)?;
+                Ok(Transaction {
+                    id: row.get(0)?,
+                    provider: row.get(1)?,
+                    started: from_seconds(row.get(2)?),
+                    finished: row.get::<_, Option<i64>>(3)?.map(from_seconds),
+                    status: Status::from_str(&status).unwrap_or(Status::Failed),
+                    exit_code: row.get(5)?,
+                    entries: Vec::new(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<Transaction>>>()?;
+
+        let mut select = self.connection.prepare(
+            "SELECT package, action, version_before, version_after
+            FROM transaction_packages WHERE transaction_id = ?1 ORDER BY rowid",
+        )?;
+        for transaction in &mut transactions {
+            transaction.entries = select
+                .query_map([transaction.id], |row| {
+                    let action: String = row.get(1)?;
+                    Ok(Entry {
+                        package: row.get(0)?,
+                        action: Action::from_str(&action).unwrap_or(Action::Update),
+                        version_before: row.get(2)?,
+                        version_after: row.get(3)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<Entry>>>()?;
+        }
+        Ok(transactions)
+    }
+
+    /// What the commands of the transaction printed.
+    pub fn transcript(&self, id: i64) -> Result<String> {
+        Ok(self.connection.query_row(
+            "SELECT transcript FROM transactions WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )?)
+    }
+}
+
+fn to_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+fn from_seconds(seconds: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64)
+}
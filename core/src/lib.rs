@@ -0,0 +1,22 @@
+//! Package providers and the commands they run, without any GTK dependency.
+
+pub mod ansi;
+pub mod api;
+pub mod catalog;
+pub mod command;
+pub mod details;
+pub mod error;
+pub mod executor;
+pub mod history;
+pub mod package;
+pub mod privilege;
+pub mod progress;
+pub mod provider;
+pub mod providers_impl;
+pub mod query;
+pub mod search;
+pub mod settings;
+pub mod utils;
+
+/// Names the directory of the settings and downloaded files.
+pub static APP_ID: &str = "org.caioxcezar.packagemanager";
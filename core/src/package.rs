@@ -0,0 +1,74 @@
+use serde::Serialize;
+use strum_macros::{Display, EnumString};
+
+use crate::utils;
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Display, EnumString, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageStatus {
+    #[default]
+    #[strum(to_string = "Not installed")]
+    NotInstalled,
+    #[strum(to_string = "Up to date")]
+    UpToDate,
+    #[strum(to_string = "Update available")]
+    UpdateAvailable,
+    #[strum(to_string = "Newer than repository")]
+    NewerThanRepo,
+    #[strum(to_string = "Foreign")]
+    Foreign,
+}
+
+impl PackageStatus {
+    pub fn from_versions(installed: Option<&str>, available: &str) -> Self {
+        match installed {
+            None => PackageStatus::NotInstalled,
+            Some(installed) => match utils::version_cmp(installed, available) {
+                std::cmp::Ordering::Less => PackageStatus::UpdateAvailable,
+                std::cmp::Ordering::Equal => PackageStatus::UpToDate,
+                std::cmp::Ordering::Greater => PackageStatus::NewerThanRepo,
+            },
+        }
+    }
+
+    pub fn is_installed(&self) -> bool {
+        !matches!(self, PackageStatus::NotInstalled)
+    }
+}
+
+#[derive(Default, Clone, Debug, Serialize)]
+pub struct PackageData {
+    pub status: PackageStatus,
+    pub repository: String,
+    pub name: String,
+    pub installed_version: Option<String>,
+    pub available_version: Option<String>,
+    pub qualified_name: String,
+    // What the providers know besides the versions, unset when they don't
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Bytes taken once installed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installed_size: Option<u64>,
+    /// Bytes to download to install it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_size: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packager: Option<String>,
+    /// Seconds since the Unix epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub install_date: Option<i64>,
+    /// Popularity in the AUR.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub popularity: Option<f64>,
+}
+
+impl PackageData {
+    pub fn installed(&self) -> bool {
+        self.status.is_installed()
+    }
+}
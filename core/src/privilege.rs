@@ -0,0 +1,130 @@
+use anyhow::Result;
+use secstr::SecVec;
+use serde::{Deserialize, Serialize};
+use std::{env, path::Path, sync::OnceLock};
+use strum_macros::Display;
+
+use super::{
+    command::{self, Cmd, CommandStream},
+    settings, utils,
+};
+
+/// Installed next to the polkit policy, runs the package manager given as first argument.
+pub const HELPER: &str = "/usr/lib/package-manager/package-manager-helper";
+
+/// How many times a wrong password can be entered before giving up.
+pub const PASSWORD_ATTEMPTS: usize = 3;
+
+/// How commands that need root are run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize)]
+pub enum Privilege {
+    /// The application already runs as root.
+    Root,
+    /// `pkexec` with the helper, authorised by the polkit agent of the session.
+    Polkit,
+    /// `sudo -A`, asking through the program in `SUDO_ASKPASS`.
+    SudoAskpass,
+    /// `sudo -S`, with the password asked by the application.
+    Sudo,
+}
+
+impl Privilege {
+    /// The privilege chosen in the settings, otherwise the most convenient one available.
+    pub fn current() -> Privilege {
+        static CURRENT: OnceLock<Privilege> = OnceLock::new();
+        *CURRENT.get_or_init(|| {
+            settings::get()
+                .ok()
+                .and_then(|settings| settings.privilege)
+                .unwrap_or_else(Privilege::detect)
+        })
+    }
+
+    pub fn detect() -> Privilege {
+        if is_root() {
+            Privilege::Root
+        } else if Path::new(HELPER).exists()
+            && command::run(&Cmd::new("pkexec").arg("--version")).is_ok()
+        {
+            Privilege::Polkit
+        } else if env::var_os("SUDO_ASKPASS").is_some_and(|path| Path::new(&path).exists()) {
+            Privilege::SudoAskpass
+        } else {
+            Privilege::Sudo
+        }
+    }
+
+    /// Whether the application has to ask for the password and pipe it to the command.
+    pub fn needs_password(&self) -> bool {
+        *self == Privilege::Sudo
+    }
+
+    /// Wraps `command` so it runs as root.
+    pub fn elevate(&self, command: Cmd) -> Cmd {
+        match self {
+            Privilege::Root => command,
+            Privilege::Polkit => command.wrap("pkexec", &[HELPER]),
+            Privilege::SudoAskpass => command.wrap("sudo", &["-A"]),
+            Privilege::Sudo => command.wrap("sudo", &["-S"]),
+        }
+    }
+
+    /// Arguments for tools such as paru that run `sudo` themselves.
+    pub fn sudo_flags(&self) -> Vec<&'static str> {
+        match self {
+            Privilege::Root => Vec::new(),
+            Privilege::Polkit => vec!["--sudo", "pkexec", "--sudoflags", HELPER],
+            Privilege::SudoAskpass => vec!["--sudoflags", "-A", "--sudoloop"],
+            Privilege::Sudo => vec!["--sudoflags", "-S", "--sudoloop"],
+        }
+    }
+
+    /// Caches the sudo credentials up front, for tools that call sudo more than once.
+    pub fn validate(&self) -> Option<Cmd> {
+        match self {
+            Privilege::SudoAskpass => Some(Cmd::new("sudo").args(["-A", "-v"])),
+            Privilege::Sudo => Some(Cmd::new("sudo").args(["-S", "-v"])),
+            Privilege::Root | Privilege::Polkit => None,
+        }
+    }
+
+    /// What to write to the command when it asks for the password.
+    pub fn input(&self, password: Option<SecVec<u8>>) -> Result<Option<Vec<String>>> {
+        if self.needs_password() {
+            Ok(Some(utils::pass_2_stdin(password)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Fails when sudo rejects the password. Cached credentials are ignored, then dropped.
+    pub fn verify(&self, password: SecVec<u8>) -> Result<()> {
+        if !self.needs_password() {
+            return Ok(());
+        }
+        let mut stream = CommandStream::new(
+            Cmd::new("sudo").args(["-k", "-S", "-p", "", "-v"]),
+            Some(utils::pass_2_stdin(Some(password))?),
+        )?;
+        let result = stream.close().map(|_| ());
+        self.reset();
+        result
+    }
+
+    /// Drops cached sudo credentials, so the piped password is always the one asked for.
+    pub fn reset(&self) {
+        if self.needs_password() {
+            let _ = command::run(&Cmd::new("sudo").arg("-k"));
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_root() -> bool {
+    command::run(&Cmd::new("id").arg("-u")).is_ok_and(|uid| uid.trim() == "0")
+}
+
+#[cfg(not(unix))]
+fn is_root() -> bool {
+    false
+}
@@ -0,0 +1,181 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::ansi;
+
+/// `( 3/12) installing foo [#####---] 45%`
+static PACMAN_STEP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*\(\s*(\d+)/(\d+)\)\s*(.*?)(?:\s+\[[^\]]*\]\s*(\d{1,3})%)?\s*$")
+        .expect("Invalid regex")
+});
+/// `Total ( 2/5)  100.0 MiB  3.0 MiB/s 00:10 [####---] 40%`, the overall download progress
+static PACMAN_TOTAL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*Total\s+\(\s*(\d+)/(\d+)\).*\s(\d{1,3})%\s*$").expect("Invalid regex")
+});
+/// ` foo-1.0-1-x86_64  1.2 MiB  3.4 MiB/s 00:01 [#####---] 45%`
+static PACMAN_DOWNLOAD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(\S+)\s.*\[[^\]]*\]\s*(\d{1,3})%\s*$").expect("Invalid regex")
+});
+/// `(3/12): foo-1.0.rpm  45% [===  ]` (dnf 4) and `[ 3/12] foo-1.0.rpm  45% |` (dnf 5)
+static DNF_DOWNLOAD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*[(\[]\s*(\d+)/(\d+)[)\]]:?\s+(\S+)(?:.*?\s(\d{1,3})%)?")
+        .expect("Invalid regex")
+});
+/// `  Installing   : foo-1.0.x86_64   3/12`
+static DNF_TRANSACTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*([A-Z][\w ]*?)\s*:\s*(\S+)\s+(\d+)/(\d+)\s*$").expect("Invalid regex")
+});
+/// `Installing 2/5… ████▌  45%  1.2 MB/s`
+static FLATPAK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*([A-Z]\w+)(?:\s+(\d+)/(\d+))?….*?(\d{1,3})%").expect("Invalid regex")
+});
+/// `  3072K ........ ........ ........ 45% 1.2M 2s` (wget dot output)
+static WGET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*\d+[KMG]\s[ .]*\s(\d{1,3})%").expect("Invalid regex"));
+static PERCENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d{1,3}(?:\.\d+)?)%").expect("Invalid regex"));
+
+/// Progress recognised in a line of command output.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Progress {
+    /// What is being worked on, e.g. the package being downloaded.
+    pub item: Option<String>,
+    /// Position of the item in the transaction, as `(current, total)`.
+    pub step: Option<(usize, usize)>,
+    /// Overall completion between 0 and 1.
+    pub fraction: f64,
+}
+
+impl Progress {
+    fn new(item: Option<&str>, step: Option<(usize, usize)>, percent: Option<f64>) -> Self {
+        let percent = percent.unwrap_or(0.0).clamp(0.0, 100.0) / 100.0;
+        let fraction = match step {
+            // The current step is done by `percent`, the previous ones completely
+            Some((current, total)) if total > 0 => {
+                ((current.saturating_sub(1) as f64 + percent) / total as f64).min(1.0)
+            }
+            _ => percent,
+        };
+        Progress {
+            item: item
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(String::from),
+            step,
+            fraction,
+        }
+    }
+
+    /// A short description such as `installing foo (3/12)`.
+    pub fn label(&self) -> String {
+        let item = self.item.clone().unwrap_or_default();
+        match self.step {
+            Some((current, total)) => format!("{item} ({current}/{total})").trim().to_string(),
+            None => item,
+        }
+    }
+}
+
+/// How the output of a provider reports its progress.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProgressFormat {
+    Pacman,
+    Dnf,
+    Flatpak,
+    Wget,
+    /// Any percentage found in the line.
+    #[default]
+    Percent,
+}
+
+impl ProgressFormat {
+    pub fn parse(&self, line: &str) -> Option<Progress> {
+        let line = ansi::strip(line);
+        match self {
+            ProgressFormat::Pacman => parse_pacman(&line),
+            ProgressFormat::Dnf => parse_dnf(&line),
+            ProgressFormat::Flatpak => parse_flatpak(&line),
+            ProgressFormat::Wget => parse_wget(&line),
+            ProgressFormat::Percent => None,
+        }
+        .or_else(|| parse_percent(&line))
+    }
+}
+
+fn number<T: std::str::FromStr>(capture: Option<regex::Match>) -> Option<T> {
+    capture.and_then(|value| value.as_str().parse().ok())
+}
+
+fn step(current: Option<regex::Match>, total: Option<regex::Match>) -> Option<(usize, usize)> {
+    Some((number(current)?, number(total)?))
+}
+
+fn parse_pacman(line: &str) -> Option<Progress> {
+    if let Some(captures) = PACMAN_TOTAL.captures(line) {
+        return Some(Progress {
+            step: step(captures.get(1), captures.get(2)),
+            ..Progress::new(Some("downloading"), None, number(captures.get(3)))
+        });
+    }
+    if let Some(captures) = PACMAN_STEP.captures(line) {
+        let step = step(captures.get(1), captures.get(2));
+        // Without a bar the step has only started
+        let percent = number(captures.get(4));
+        return Some(Progress::new(
+            captures.get(3).map(|item| item.as_str()),
+            step,
+            percent,
+        ));
+    }
+    let captures = PACMAN_DOWNLOAD.captures(line)?;
+    Some(Progress::new(
+        captures.get(1).map(|item| item.as_str()),
+        None,
+        number(captures.get(2)),
+    ))
+}
+
+fn parse_dnf(line: &str) -> Option<Progress> {
+    if let Some(captures) = DNF_TRANSACTION.captures(line) {
+        let item = format!(
+            "{} {}",
+            captures[1].to_lowercase(),
+            captures.get(2).map_or("", |item| item.as_str())
+        );
+        // The step is reported once it is done
+        return Some(Progress::new(
+            Some(&item),
+            step(captures.get(3), captures.get(4)),
+            Some(100.0),
+        ));
+    }
+    let captures = DNF_DOWNLOAD.captures(line)?;
+    Some(Progress::new(
+        captures.get(3).map(|item| item.as_str()),
+        step(captures.get(1), captures.get(2)),
+        number(captures.get(4)),
+    ))
+}
+
+fn parse_flatpak(line: &str) -> Option<Progress> {
+    let captures = FLATPAK.captures(line)?;
+    Some(Progress::new(
+        captures.get(1).map(|item| item.as_str()),
+        step(captures.get(2), captures.get(3)),
+        number(captures.get(4)),
+    ))
+}
+
+fn parse_wget(line: &str) -> Option<Progress> {
+    let captures = WGET.captures(line)?;
+    Some(Progress::new(
+        Some("downloading"),
+        None,
+        number(captures.get(1)),
+    ))
+}
+
+fn parse_percent(line: &str) -> Option<Progress> {
+    let captures = PERCENT.captures(line)?;
+    Some(Progress::new(None, None, number(captures.get(1))))
+}
@@ -0,0 +1,216 @@
+use crate::providers_impl::soar::Soar;
+
+use super::{
+    command::CommandStream,
+    details::{self, PackageDetails},
+    error::Result,
+    package::{PackageData, PackageStatus},
+    privilege::Privilege,
+    progress::ProgressFormat,
+    providers_impl::{
+        dnf::Dnf, flatpak::Flatpak, pacman::Pacman, paru::Paru, protonge::ProtonGE, winget::Winget,
+    },
+};
+use anyhow::anyhow;
+use rayon::prelude::*;
+use secstr::SecVec;
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+#[derive(Debug, EnumIter, Clone)]
+pub enum ProviderKind {
+    Flatpak(Flatpak),
+    Pacman(Pacman),
+    Paru(Paru),
+    ProtonGE(ProtonGE),
+    Winget(Winget),
+    Dnf(Dnf),
+    Soar(Soar),
+}
+
+impl ProviderKind {
+    fn as_provider_actions(&self) -> &dyn ProviderActions {
+        match self {
+            ProviderKind::Flatpak(provider) => provider,
+            ProviderKind::Pacman(provider) => provider,
+            ProviderKind::Paru(provider) => provider,
+            ProviderKind::ProtonGE(provider) => provider,
+            ProviderKind::Winget(provider) => provider,
+            ProviderKind::Dnf(provider) => provider,
+            ProviderKind::Soar(provider) => provider,
+        }
+    }
+    fn as_mut_provider_actions(&mut self) -> &mut dyn ProviderActions {
+        match self {
+            ProviderKind::Flatpak(provider) => provider,
+            ProviderKind::Pacman(provider) => provider,
+            ProviderKind::Paru(provider) => provider,
+            ProviderKind::ProtonGE(provider) => provider,
+            ProviderKind::Winget(provider) => provider,
+            ProviderKind::Dnf(provider) => provider,
+            ProviderKind::Soar(provider) => provider,
+        }
+    }
+    pub fn is_available(&self) -> bool {
+        self.as_provider_actions().is_available()
+    }
+    pub fn name(&self) -> String {
+        self.as_provider_actions().name()
+    }
+    pub fn is_root_required(&self) -> bool {
+        self.as_provider_actions().is_root_required()
+    }
+    pub fn progress_format(&self) -> ProgressFormat {
+        self.as_provider_actions().progress_format()
+    }
+    pub fn package_info(&self, package_name: String) -> Result<String> {
+        self.as_provider_actions().package_info(package_name)
+    }
+    pub fn package_details(&self, package_name: String) -> Result<PackageDetails> {
+        self.as_provider_actions().package_details(package_name)
+    }
+    pub fn update(&self, password: Option<SecVec<u8>>) -> Result<CommandStream> {
+        Privilege::current().reset();
+        self.as_provider_actions().update(password)
+    }
+    pub fn install(&self, password: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        Privilege::current().reset();
+        self.as_provider_actions().install(password, package)
+    }
+    pub fn remove(&self, password: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        Privilege::current().reset();
+        self.as_provider_actions().remove(password, package)
+    }
+    pub fn execute(
+        &self,
+        password: Option<SecVec<u8>>,
+        changes: Vec<Change>,
+    ) -> Result<CommandStream> {
+        match changes.as_slice() {
+            [change] => match change.operation {
+                Operation::Install => self.install(password, change.package.clone()),
+                Operation::Remove => self.remove(password, change.package.clone()),
+            },
+            _ => {
+                Privilege::current().reset();
+                self.as_provider_actions().execute(password, changes)
+            }
+        }
+    }
+    pub fn upgradable(&self) -> Result<Vec<PackageData>> {
+        self.as_provider_actions().upgradable()
+    }
+    pub fn can_downgrade(&self) -> bool {
+        self.as_provider_actions().can_downgrade()
+    }
+    pub fn downgrade(
+        &self,
+        password: Option<SecVec<u8>>,
+        packages: Vec<(String, String)>,
+    ) -> Result<CommandStream> {
+        Privilege::current().reset();
+        self.as_provider_actions().downgrade(password, packages)
+    }
+    pub fn update_selected(
+        &self,
+        password: Option<SecVec<u8>>,
+        packages: Vec<String>,
+    ) -> Result<CommandStream> {
+        Privilege::current().reset();
+        self.as_provider_actions()
+            .update_selected(password, packages)
+    }
+    pub fn packages(&self) -> Vec<PackageData> {
+        self.as_provider_actions().packages()
+    }
+    pub fn update_packages(&mut self) -> Result<()> {
+        self.as_mut_provider_actions().load_packages()
+    }
+    /// The providers installed on this system, checked in parallel as each check runs a command.
+    pub fn available_providers() -> Vec<ProviderKind> {
+        ProviderKind::iter()
+            .collect::<Vec<ProviderKind>>()
+            .into_par_iter()
+            .filter(|provider_kind| provider_kind.is_available())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum Operation {
+    Install,
+    Remove,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub operation: Operation,
+    pub package: String,
+}
+
+/// Splits a list of changes into the packages to install and the packages to remove.
+pub fn split_changes(changes: &[Change]) -> (Vec<String>, Vec<String>) {
+    let packages = |operation: Operation| {
+        changes
+            .iter()
+            .filter(|change| change.operation == operation)
+            .map(|change| change.package.clone())
+            .collect::<Vec<String>>()
+    };
+    (packages(Operation::Install), packages(Operation::Remove))
+}
+
+pub trait ProviderActions {
+    fn load_packages(&mut self) -> Result<()>;
+    fn is_available(&self) -> bool;
+    fn name(&self) -> String;
+    fn is_root_required(&self) -> bool;
+    /// How progress is reported in the output of the provider's commands.
+    fn progress_format(&self) -> ProgressFormat {
+        ProgressFormat::default()
+    }
+    fn packages(&self) -> Vec<PackageData>;
+    fn package_info(&self, package: String) -> Result<String>;
+    /// [`package_info`](Self::package_info) laid out in fields, for providers printing
+    /// `Label : value` lines.
+    fn package_details(&self, package: String) -> Result<PackageDetails> {
+        let raw = self.package_info(package)?;
+        let fields = details::parse_fields(&raw);
+        Ok(PackageDetails::from_fields(raw, fields))
+    }
+    fn install(&self, password: Option<SecVec<u8>>, package: String) -> Result<CommandStream>;
+    fn remove(&self, password: Option<SecVec<u8>>, package: String) -> Result<CommandStream>;
+    fn update(&self, password: Option<SecVec<u8>>) -> Result<CommandStream>;
+    /// Runs every change as a single transaction (one command per operation where possible).
+    fn execute(&self, password: Option<SecVec<u8>>, changes: Vec<Change>) -> Result<CommandStream>;
+    fn update_selected(
+        &self,
+        password: Option<SecVec<u8>>,
+        packages: Vec<String>,
+    ) -> Result<CommandStream>;
+    /// Packages with a pending upgrade, based on the last loaded package list unless the
+    /// provider has a dedicated command for it.
+    fn upgradable(&self) -> Result<Vec<PackageData>> {
+        Ok(self
+            .packages()
+            .into_iter()
+            .filter(|package| package.status == PackageStatus::UpdateAvailable)
+            .collect())
+    }
+    /// Whether [`downgrade`](Self::downgrade) can install older versions.
+    fn can_downgrade(&self) -> bool {
+        false
+    }
+    /// Installs the given version of each package, e.g. to undo an update.
+    fn downgrade(
+        &self,
+        _password: Option<SecVec<u8>>,
+        _packages: Vec<(String, String)>,
+    ) -> Result<CommandStream> {
+        Err(anyhow!("{} can't install older versions", self.name()).into())
+    }
+    #[allow(dead_code)]
+    fn installed(&self) -> usize;
+    #[allow(dead_code)]
+    fn total(&self) -> usize;
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+use regex::Regex;
+use secstr::SecVec;
+
+use crate::{
+    command::{Cmd, CommandStream},
+    error::Result,
+    executor::{self, SharedExecutor},
+    package::{PackageData, PackageStatus},
+    privilege::Privilege,
+    progress::ProgressFormat,
+    provider::{split_changes, Change, ProviderActions},
+};
+/// Repositories dnf reports for packages that did not come from a configured repo.
+const FOREIGN_REPOSITORIES: [&str; 2] = ["commandline", "System"];
+
+#[derive(Clone, Debug)]
+pub struct Dnf {
+    name: String,
+    packages: Vec<PackageData>,
+    installed: usize,
+    total: usize,
+    root_required: bool,
+    executor: SharedExecutor,
+}
+
+impl Default for Dnf {
+    fn default() -> Self {
+        Dnf {
+            name: String::from("Dnf"),
+            packages: Vec::new(),
+            root_required: true,
+            executor: executor::system(),
+            installed: 0,
+            total: 0,
+        }
+    }
+}
+
+impl Dnf {
+    pub fn with_executor(executor: SharedExecutor) -> Self {
+        Dnf {
+            executor,
+            ..Default::default()
+        }
+    }
+}
+
+impl ProviderActions for Dnf {
+    fn installed(&self) -> usize {
+        self.installed
+    }
+    fn total(&self) -> usize {
+        self.total
+    }
+    fn is_root_required(&self) -> bool {
+        self.root_required
+    }
+    fn progress_format(&self) -> ProgressFormat {
+        ProgressFormat::Dnf
+    }
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    fn packages(&self) -> Vec<PackageData> {
+        self.packages.clone()
+    }
+    fn load_packages(&mut self) -> Result<()> {
+        self.packages.clear();
+
+        let packages = self.executor.run(&list_command(["list", "--all", "-q"]))?;
+        let packages = unwrap_lines(&packages);
+
+        // dnf5 writes the headers in lowercase
+        let available_header = Regex::new(r"(?mi)^available packages\n").expect("Invalid regex");
+        let installed_header = Regex::new(r"(?mi)^installed packages\n").expect("Invalid regex");
+        let grp_packages = available_header.split(&packages).collect::<Vec<&str>>();
+
+        let seperator = Regex::new(r"[\s,]+").expect("Invalid regex");
+        let parse = |packages: &str| -> Vec<(String, String, String)> {
+            packages
+                .par_split('\n')
+                .filter_map(|package| {
+                    let list_package: Vec<&str> = seperator.split(package.trim()).collect();
+                    if list_package.len() != 3 {
+                        return None;
+                    }
+                    Some((
+                        String::from(list_package[0].trim()),
+                        String::from(list_package[1].trim()),
+                        String::from(list_package[2].trim()),
+                    ))
+                })
+                .collect()
+        };
+
+        let installed = grp_packages
+            .first()
+            .map(|packages| parse(&installed_header.replace(packages, "")))
+            .unwrap_or_default();
+        let mut installed: HashMap<String, (String, String)> = installed
+            .into_iter()
+            .map(|(name, version, repository)| (name, (version, repository)))
+            .collect();
+        let available = grp_packages
+            .get(1)
+            .map(|packages| parse(packages))
+            .unwrap_or_default();
+
+        let mut available_packages: Vec<PackageData> = available
+            .into_iter()
+            .map(|(name, version, repository)| {
+                let installed_version = installed.remove(&name).map(|(version, _)| version);
+                PackageData {
+                    status: PackageStatus::from_versions(installed_version.as_deref(), &version),
+                    repository,
+                    qualified_name: name.clone(),
+                    name,
+                    installed_version,
+                    available_version: Some(version),
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        self.packages = installed
+            .into_iter()
+            .map(|(name, (version, repository))| {
+                let repository = repository.trim_start_matches('@').to_string();
+                let status = if FOREIGN_REPOSITORIES.contains(&repository.as_str()) {
+                    PackageStatus::Foreign
+                } else {
+                    PackageStatus::UpToDate
+                };
+                PackageData {
+                    status,
+                    repository,
+                    qualified_name: name.clone(),
+                    name,
+                    installed_version: Some(version.clone()),
+                    available_version: (status != PackageStatus::Foreign).then_some(version),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        self.packages.append(&mut available_packages);
+
+        self.installed = self.packages.par_iter().filter(|&p| p.installed()).count();
+        self.total = self.packages.len();
+        Ok(())
+    }
+    fn package_info(&self, package: String) -> Result<String> {
+        self.executor
+            .run(&Cmd::new("dnf").arg("info").packages([package])?)
+    }
+    fn install(&self, password: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        let privilege = Privilege::current();
+        self.executor.stream(
+            privilege.elevate(
+                Cmd::new("dnf")
+                    .arg("install")
+                    .packages([package])?
+                    .arg("-y"),
+            ),
+            privilege.input(password)?,
+        )
+    }
+    fn remove(&self, password: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        let privilege = Privilege::current();
+        self.executor.stream(
+            privilege.elevate(Cmd::new("dnf").arg("remove").packages([package])?.arg("-y")),
+            privilege.input(password)?,
+        )
+    }
+    fn update(&self, password: Option<SecVec<u8>>) -> Result<CommandStream> {
+        let privilege = Privilege::current();
+        self.executor.stream(
+            privilege.elevate(Cmd::new("dnf").args(["update", "-y"])),
+            privilege.input(password)?,
+        )
+    }
+    fn execute(&self, password: Option<SecVec<u8>>, changes: Vec<Change>) -> Result<CommandStream> {
+        let privilege = Privilege::current();
+        let (install, remove) = split_changes(&changes);
+        let mut commands = Vec::new();
+        if !install.is_empty() {
+            commands.push(
+                privilege.elevate(Cmd::new("dnf").arg("install").packages(install)?.arg("-y")),
+            );
+        }
+        if !remove.is_empty() {
+            commands
+                .push(privilege.elevate(Cmd::new("dnf").arg("remove").packages(remove)?.arg("-y")));
+        }
+        self.executor
+            .stream(Cmd::chain(&commands), privilege.input(password)?)
+    }
+    fn update_selected(
+        &self,
+        password: Option<SecVec<u8>>,
+        packages: Vec<String>,
+    ) -> Result<CommandStream> {
+        let privilege = Privilege::current();
+        self.executor.stream(
+            privilege.elevate(Cmd::new("dnf").arg("upgrade").packages(packages)?.arg("-y")),
+            privilege.input(password)?,
+        )
+    }
+    fn can_downgrade(&self) -> bool {
+        true
+    }
+    fn downgrade(
+        &self,
+        password: Option<SecVec<u8>>,
+        packages: Vec<(String, String)>,
+    ) -> Result<CommandStream> {
+        // `name.arch` at a version is spelled `name-version.arch`
+        let packages = packages
+            .iter()
+            .map(|(package, version)| match package.rsplit_once('.') {
+                Some((name, arch)) => format!("{name}-{version}.{arch}"),
+                None => format!("{package}-{version}"),
+            });
+        let privilege = Privilege::current();
+        self.executor.stream(
+            privilege.elevate(
+                Cmd::new("dnf")
+                    .arg("downgrade")
+                    .packages(packages)?
+                    .arg("-y"),
+            ),
+            privilege.input(password)?,
+        )
+    }
+    fn upgradable(&self) -> Result<Vec<PackageData>> {
+        // dnf check-update exits with 100 when there are updates available
+        let output = self
+            .executor
+            .run_with_codes(&list_command(["check-update", "-q"]), &[0, 100])?;
+        let output = unwrap_lines(&output);
+        let seperator = Regex::new(r"\s+").expect("Invalid regex");
+
+        Ok(output
+            .lines()
+            .take_while(|line| !line.starts_with("Obsoleting Packages"))
+            .filter_map(|line| {
+                let list_package: Vec<&str> = seperator.split(line.trim()).collect();
+                if list_package.len() != 3 {
+                    return None;
+                }
+                let name = list_package[0].to_string();
+                let installed_version = self
+                    .packages
+                    .iter()
+                    .find(|package| package.name == name)
+                    .and_then(|package| package.installed_version.clone());
+                Some(PackageData {
+                    status: PackageStatus::UpdateAvailable,
+                    repository: list_package[2].to_string(),
+                    qualified_name: name.clone(),
+                    name,
+                    installed_version,
+                    available_version: Some(list_package[1].to_string()),
+                    ..Default::default()
+                })
+            })
+            .collect())
+    }
+    fn is_available(&self) -> bool {
+        let packages = self.executor.run(&Cmd::new("dnf").arg("--version"));
+        packages.is_ok()
+    }
+}
+
+/// dnf with untranslated output, for the commands that are parsed.
+fn list_command<const N: usize>(args: [&str; N]) -> Cmd {
+    Cmd::new("dnf").args(args).env("LC_ALL", "C")
+}
+
+/// dnf wraps long package names, continuing the row on an indented line.
+fn unwrap_lines(output: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for line in output.lines() {
+        match lines.last_mut() {
+            Some(row)
+                if line.starts_with([' ', '\t'])
+                    && !line.trim().is_empty()
+                    && !row.trim().is_empty() =>
+            {
+                row.push(' ');
+                row.push_str(line.trim_start());
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines.join("\n")
+}
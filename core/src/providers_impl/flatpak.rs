@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use rayon::prelude::*;
+use secstr::SecVec;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command::{Cmd, CommandStream},
+    details::PackageDetails,
+    error::{ProviderError, Result},
+    executor::{self, SharedExecutor},
+    package::{PackageData, PackageStatus},
+    progress::ProgressFormat,
+    provider::{split_changes, Change, ProviderActions},
+    utils,
+};
+
+const NONINTERACTIVE: [&str; 2] = ["-y", "--noninteractive"];
+
+#[derive(Clone, Debug)]
+pub struct Flatpak {
+    name: String,
+    packages: Vec<PackageData>,
+    installed: usize,
+    total: usize,
+    root_required: bool,
+    executor: SharedExecutor,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FlatpakPackage {
+    name: String,
+    #[serde(rename = "ref")]
+    application_ref: String,
+    branch: String,
+    version: String,
+    origin: String,
+    arch: String,
+    #[serde(default)]
+    description: String,
+    /// Only listed by `flatpak list`.
+    #[serde(default)]
+    size: Option<String>,
+    /// Only listed by `flatpak remote-ls`.
+    #[serde(default, rename = "installed-size")]
+    installed_size: Option<String>,
+    #[serde(default, rename = "download-size")]
+    download_size: Option<String>,
+}
+
+impl FlatpakPackage {
+    /// The description and sizes, the size on disk coming from `installed` when it is.
+    fn metadata(&self, installed: Option<&FlatpakPackage>) -> PackageData {
+        let installed_size = installed
+            .and_then(|installed| installed.size.as_deref())
+            .or(self.installed_size.as_deref())
+            .or(self.size.as_deref());
+        PackageData {
+            description: Some(self.description.clone()).filter(|text| !text.is_empty()),
+            installed_size: installed_size.and_then(utils::parse_size),
+            download_size: self.download_size.as_deref().and_then(utils::parse_size),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for Flatpak {
+    fn default() -> Self {
+        Flatpak {
+            name: String::from("Flatpak"),
+            packages: Vec::new(),
+            root_required: false,
+            executor: executor::system(),
+            installed: 0,
+            total: 0,
+        }
+    }
+}
+
+impl Flatpak {
+    pub fn with_executor(executor: SharedExecutor) -> Self {
+        Flatpak {
+            executor,
+            ..Default::default()
+        }
+    }
+}
+
+impl ProviderActions for Flatpak {
+    fn installed(&self) -> usize {
+        self.installed
+    }
+    fn total(&self) -> usize {
+        self.total
+    }
+    fn is_root_required(&self) -> bool {
+        self.root_required
+    }
+    fn progress_format(&self) -> ProgressFormat {
+        ProgressFormat::Flatpak
+    }
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    fn packages(&self) -> Vec<PackageData> {
+        self.packages.clone()
+    }
+    fn load_packages(&mut self) -> Result<()> {
+        self.packages.clear();
+
+        let packages: String = self.executor.run(&list_command("list"))?;
+        let installed_packages: Vec<FlatpakPackage> =
+            serde_json::from_str(&packages).map_err(|err| ProviderError::json(&packages, err))?;
+        let mut installed_versions: HashMap<&str, &FlatpakPackage> = installed_packages
+            .par_iter()
+            .map(|f| (f.application_ref.as_str(), f))
+            .collect();
+
+        let output = self.executor.run(&list_command("remote-ls"))?;
+        let packages: Vec<FlatpakPackage> =
+            serde_json::from_str(&output).map_err(|err| ProviderError::json(&output, err))?;
+        self.packages.append(
+            &mut packages
+                .par_iter()
+                .map(|pkg| {
+                    let installed = installed_versions
+                        .get(pkg.application_ref.as_str())
+                        .copied();
+                    let installed_version = installed.map(|installed| installed.version.clone());
+                    let status = match &installed_version {
+                        // Flatpak versions are free-form and often empty, so only compare when both are set
+                        Some(version) if version.is_empty() || pkg.version.is_empty() => {
+                            PackageStatus::UpToDate
+                        }
+                        _ => {
+                            PackageStatus::from_versions(installed_version.as_deref(), &pkg.version)
+                        }
+                    };
+                    PackageData {
+                        status,
+                        repository: format!("{} {} {}", pkg.origin, pkg.branch, pkg.arch),
+                        name: pkg.name.clone(),
+                        qualified_name: format!("{} {}", pkg.origin, pkg.application_ref),
+                        installed_version,
+                        available_version: Some(pkg.version.clone()),
+                        ..pkg.metadata(installed)
+                    }
+                })
+                .collect::<Vec<PackageData>>(),
+        );
+
+        for pkg in &packages {
+            installed_versions.remove(pkg.application_ref.as_str());
+        }
+        for pkg in installed_versions.values() {
+            self.packages.push(PackageData {
+                status: PackageStatus::Foreign,
+                repository: format!("{} {} {}", pkg.origin, pkg.branch, pkg.arch),
+                name: pkg.name.clone(),
+                qualified_name: format!("{} {}", pkg.origin, pkg.application_ref),
+                installed_version: Some(pkg.version.clone()),
+                available_version: None,
+                ..pkg.metadata(None)
+            });
+        }
+
+        self.installed = installed_packages.len();
+        self.total = self.packages.len();
+        Ok(())
+    }
+    fn package_info(&self, package: String) -> Result<String> {
+        let (origin, application_ref) = split_qualified_name(&package)?;
+        let response = self.executor.run(
+            &Cmd::new("flatpak")
+                .arg("remote-info")
+                .packages([origin, application_ref])?,
+        )?;
+        Ok(response)
+    }
+    fn package_details(&self, package: String) -> Result<PackageDetails> {
+        let raw = self.package_info(package)?;
+        // The labels are aligned to the right
+        let fields = raw
+            .lines()
+            .filter_map(|line| line.trim().split_once(": "))
+            .map(|(label, value)| (label.to_string(), value.trim().to_string()))
+            .collect();
+        let mut details = PackageDetails::from_fields(raw, fields);
+        // The runtime is what an application needs installed
+        if let Some(position) = details
+            .fields
+            .iter()
+            .position(|(label, _)| label == "Runtime")
+        {
+            let (_, runtime) = details.fields.remove(position);
+            details.dependencies.push(runtime);
+        }
+        Ok(details)
+    }
+    fn install(&self, _: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        let (origin, application_ref) = split_qualified_name(&package)?;
+        self.executor.stream(
+            Cmd::new("flatpak")
+                .arg("install")
+                .packages([origin, application_ref])?
+                .args(NONINTERACTIVE),
+            None,
+        )
+    }
+    fn remove(&self, _: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        let (_, application_ref) = split_qualified_name(&package)?;
+        self.executor.stream(
+            Cmd::new("flatpak")
+                .arg("remove")
+                .packages([application_ref])?
+                .args(NONINTERACTIVE),
+            None,
+        )
+    }
+    fn update(&self, _: Option<SecVec<u8>>) -> Result<CommandStream> {
+        self.executor
+            .stream(Cmd::new("flatpak").arg("update").args(NONINTERACTIVE), None)
+    }
+    fn execute(&self, _: Option<SecVec<u8>>, changes: Vec<Change>) -> Result<CommandStream> {
+        let (install, remove) = split_changes(&changes);
+        let mut commands = Vec::new();
+        // flatpak install takes a single remote, so installs are grouped by origin
+        let mut origins: Vec<(String, Vec<String>)> = Vec::new();
+        for package in &install {
+            let (origin, application_ref) = split_qualified_name(package)?;
+            match origins.iter_mut().find(|(name, _)| name == origin) {
+                Some((_, refs)) => refs.push(application_ref.to_string()),
+                None => origins.push((origin.to_string(), vec![application_ref.to_string()])),
+            }
+        }
+        for (origin, refs) in origins {
+            commands.push(
+                Cmd::new("flatpak")
+                    .arg("install")
+                    .packages([origin])?
+                    .packages(refs)?
+                    .args(NONINTERACTIVE),
+            );
+        }
+        if !remove.is_empty() {
+            let refs = remove
+                .iter()
+                .map(|package| Ok(split_qualified_name(package)?.1))
+                .collect::<anyhow::Result<Vec<&str>>>()?;
+            commands.push(
+                Cmd::new("flatpak")
+                    .arg("remove")
+                    .packages(refs)?
+                    .args(NONINTERACTIVE),
+            );
+        }
+        self.executor.stream(Cmd::chain(&commands), None)
+    }
+    fn update_selected(
+        &self,
+        _: Option<SecVec<u8>>,
+        packages: Vec<String>,
+    ) -> Result<CommandStream> {
+        let refs = packages
+            .iter()
+            .map(|package| Ok(split_qualified_name(package)?.1))
+            .collect::<anyhow::Result<Vec<&str>>>()?;
+        self.executor.stream(
+            Cmd::new("flatpak")
+                .arg("update")
+                .packages(refs)?
+                .args(NONINTERACTIVE),
+            None,
+        )
+    }
+    fn upgradable(&self) -> Result<Vec<PackageData>> {
+        let output = self
+            .executor
+            .run(&list_command("remote-ls").arg("--updates"))?;
+        let packages: Vec<FlatpakPackage> =
+            serde_json::from_str(&output).map_err(|err| ProviderError::json(&output, err))?;
+        Ok(packages
+            .iter()
+            .map(|pkg| {
+                let qualified_name = format!("{} {}", pkg.origin, pkg.application_ref);
+                let installed_version = self
+                    .packages
+                    .iter()
+                    .find(|package| package.qualified_name == qualified_name)
+                    .and_then(|package| package.installed_version.clone());
+                PackageData {
+                    status: PackageStatus::UpdateAvailable,
+                    repository: format!("{} {} {}", pkg.origin, pkg.branch, pkg.arch),
+                    name: pkg.name.clone(),
+                    qualified_name,
+                    installed_version,
+                    available_version: Some(pkg.version.clone()),
+                    ..Default::default()
+                }
+            })
+            .collect())
+    }
+    fn is_available(&self) -> bool {
+        let packages = self.executor.run(&Cmd::new("flatpak").arg("--version"));
+        packages.is_ok()
+    }
+}
+
+/// `flatpak list` or `flatpak remote-ls` as JSON, with untranslated output.
+fn list_command(subcommand: &str) -> Cmd {
+    // Each lists the sizes under its own columns
+    let sizes = match subcommand {
+        "list" => "size",
+        _ => "installed-size,download-size",
+    };
+    Cmd::new("flatpak")
+        .arg(subcommand)
+        .arg(&format!(
+            "--columns=name,ref,branch,version,origin,arch,description,{sizes}"
+        ))
+        .arg("-j")
+        .env("LC_ALL", "C")
+}
+
+/// Qualified names are `origin ref`.
+fn split_qualified_name(package: &str) -> anyhow::Result<(&str, &str)> {
+    let (origin, application_ref) = package
+        .trim()
+        .split_once(' ')
+        .context("Package name not found")?;
+    Ok((origin, application_ref.trim()))
+}
@@ -0,0 +1,291 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use alpm::{Alpm, Pkg, SigLevel};
+use anyhow::anyhow;
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use secstr::SecVec;
+
+use crate::{
+    command::{Cmd, CommandStream},
+    error::Result,
+    executor::{self, SharedExecutor},
+    package::{PackageData, PackageStatus},
+    privilege::Privilege,
+    progress::ProgressFormat,
+    provider::{split_changes, Change, ProviderActions},
+    utils,
+};
+
+/// Where pacman keeps the packages it downloaded.
+const PACKAGE_CACHE: &str = "/var/cache/pacman/pkg";
+
+#[derive(Clone, Debug)]
+pub struct Pacman {
+    name: String,
+    packages: Vec<PackageData>,
+    installed: usize,
+    total: usize,
+    root_required: bool,
+    executor: SharedExecutor,
+}
+
+impl Default for Pacman {
+    fn default() -> Self {
+        Pacman {
+            name: String::from("Pacman"),
+            packages: Vec::new(),
+            root_required: true,
+            executor: executor::system(),
+            installed: 0,
+            total: 0,
+        }
+    }
+}
+
+impl Pacman {
+    pub fn with_executor(executor: SharedExecutor) -> Self {
+        Pacman {
+            executor,
+            ..Default::default()
+        }
+    }
+}
+
+impl ProviderActions for Pacman {
+    fn installed(&self) -> usize {
+        self.installed
+    }
+    fn total(&self) -> usize {
+        self.total
+    }
+    fn is_root_required(&self) -> bool {
+        self.root_required
+    }
+    fn progress_format(&self) -> ProgressFormat {
+        ProgressFormat::Pacman
+    }
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    fn packages(&self) -> Vec<PackageData> {
+        self.packages.clone()
+    }
+    fn load_packages(&mut self) -> Result<()> {
+        self.packages.clear();
+
+        let handle = Alpm::new("/", "/var/lib/pacman")?;
+        let dbs = fs::read_dir("/var/lib/pacman/sync/")?
+            .filter_map(|dir| dir.ok())
+            .filter_map(|dir| dir.file_name().into_string().ok())
+            .filter_map(|name| {
+                if name.ends_with(".db") {
+                    Some(name.trim_end_matches(".db").to_string())
+                } else {
+                    None
+                }
+            });
+
+        let mut sync_names = HashSet::new();
+        for db_name in dbs {
+            let db = handle.register_syncdb(db_name.clone(), SigLevel::NONE)?;
+            for pkg in db.pkgs() {
+                let pkg_name = pkg.name();
+                let available_version = pkg.version().to_string();
+                let local = handle.localdb().pkg(pkg_name).ok();
+                let installed_version = local.map(|local| local.version().to_string());
+                sync_names.insert(pkg_name.to_string());
+
+                self.packages.push(PackageData {
+                    status: PackageStatus::from_versions(
+                        installed_version.as_deref(),
+                        &available_version,
+                    ),
+                    repository: db_name.clone(),
+                    name: pkg_name.to_string(),
+                    qualified_name: pkg_name.to_string(),
+                    installed_version,
+                    available_version: Some(available_version),
+                    ..alpm_metadata(pkg, local.map(|local| local.as_ref()))
+                })
+            }
+        }
+
+        for pkg in handle.localdb().pkgs() {
+            if sync_names.contains(pkg.name()) {
+                continue;
+            }
+            self.packages.push(PackageData {
+                status: PackageStatus::Foreign,
+                repository: "local".to_string(),
+                name: pkg.name().to_string(),
+                qualified_name: pkg.name().to_string(),
+                installed_version: Some(pkg.version().to_string()),
+                available_version: None,
+                ..alpm_metadata(pkg, Some(pkg))
+            })
+        }
+
+        self.installed = self.packages.par_iter().filter(|p| p.installed()).count();
+        self.total = self.packages.len();
+        Ok(())
+    }
+    fn package_info(&self, package: String) -> Result<String> {
+        self.executor
+            .run(&Cmd::new("pacman").arg("-Si").packages([package])?)
+    }
+    fn install(&self, password: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        let privilege = Privilege::current();
+        self.executor.stream_pty(
+            privilege.elevate(Cmd::new("pacman").arg("-Syu").packages([package])?),
+            privilege.input(password)?,
+        )
+    }
+    fn remove(&self, password: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        let privilege = Privilege::current();
+        self.executor.stream_pty(
+            privilege.elevate(Cmd::new("pacman").arg("-Runs").packages([package])?),
+            privilege.input(password)?,
+        )
+    }
+    fn update(&self, password: Option<SecVec<u8>>) -> Result<CommandStream> {
+        let privilege = Privilege::current();
+        self.executor.stream_pty(
+            privilege.elevate(Cmd::new("pacman").arg("-Syu")),
+            privilege.input(password)?,
+        )
+    }
+    fn execute(&self, password: Option<SecVec<u8>>, changes: Vec<Change>) -> Result<CommandStream> {
+        let privilege = Privilege::current();
+        let (install, remove) = split_changes(&changes);
+        let mut commands = Vec::new();
+        if !install.is_empty() {
+            commands.push(privilege.elevate(Cmd::new("pacman").arg("-Syu").packages(install)?));
+        }
+        if !remove.is_empty() {
+            commands.push(privilege.elevate(Cmd::new("pacman").arg("-Runs").packages(remove)?));
+        }
+        self.executor
+            .stream_pty(Cmd::chain(&commands), privilege.input(password)?)
+    }
+    fn update_selected(
+        &self,
+        password: Option<SecVec<u8>>,
+        packages: Vec<String>,
+    ) -> Result<CommandStream> {
+        // No -y: the packages come from upgradable(), read from the databases -S installs from,
+        // and refreshing them for only some packages would be a partial upgrade
+        let privilege = Privilege::current();
+        self.executor.stream_pty(
+            privilege.elevate(
+                Cmd::new("pacman")
+                    .args(["-S", "--needed"])
+                    .packages(packages)?,
+            ),
+            privilege.input(password)?,
+        )
+    }
+    fn can_downgrade(&self) -> bool {
+        true
+    }
+    fn downgrade(
+        &self,
+        password: Option<SecVec<u8>>,
+        packages: Vec<(String, String)>,
+    ) -> Result<CommandStream> {
+        let files = packages
+            .iter()
+            .map(|(package, version)| cached_package(Path::new(PACKAGE_CACHE), package, version))
+            .collect::<anyhow::Result<Vec<String>>>()?;
+        let privilege = Privilege::current();
+        self.executor.stream_pty(
+            privilege.elevate(Cmd::new("pacman").arg("-U").packages(files)?),
+            privilege.input(password)?,
+        )
+    }
+    fn upgradable(&self) -> Result<Vec<PackageData>> {
+        // The synced databases, as update_selected installs from them. pacman -Qu exits with 1
+        // when up to date
+        let output = match self
+            .executor
+            .run_with_codes(&Cmd::new("pacman").arg("-Qu"), &[0, 1])
+        {
+            Ok(output) => output,
+            Err(_) => {
+                return Ok(self
+                    .packages
+                    .iter()
+                    .filter(|package| package.status == PackageStatus::UpdateAvailable)
+                    .cloned()
+                    .collect())
+            }
+        };
+        // Packages in IgnorePkg are listed with `[ignored]`
+        let output: String = output
+            .lines()
+            .filter(|line| !line.ends_with("[ignored]"))
+            .map(|line| format!("{line}\n"))
+            .collect();
+        Ok(utils::parse_upgrade_list(&output)
+            .into_iter()
+            .map(|(name, old, new)| PackageData {
+                status: PackageStatus::UpdateAvailable,
+                repository: self
+                    .packages
+                    .iter()
+                    .find(|package| package.name == name)
+                    .map(|package| package.repository.clone())
+                    .unwrap_or_default(),
+                qualified_name: name.clone(),
+                name,
+                installed_version: Some(old),
+                available_version: Some(new),
+                ..Default::default()
+            })
+            .collect())
+    }
+    fn is_available(&self) -> bool {
+        let packages = self.executor.run(&Cmd::new("pacman").arg("--version"));
+        packages.is_ok()
+    }
+}
+
+/// The file pacman downloaded `package` at `version` to, which only older versions still
+/// cached can be installed from.
+fn cached_package(cache: &Path, package: &str, version: &str) -> anyhow::Result<String> {
+    let prefix = format!("{package}-{version}-");
+    for file in fs::read_dir(cache)? {
+        let path = file?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        // e.g. `bash-5.2.026-2-x86_64.pkg.tar.zst`, not its `.sig`
+        let found = name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.split_once(".pkg.tar."))
+            .is_some_and(|(arch, extension)| !arch.contains('-') && !extension.ends_with(".sig"));
+        if found {
+            return Ok(path.to_string_lossy().into_owned());
+        }
+    }
+    Err(anyhow!(
+        "{package} {version} is no longer in the package cache {}",
+        cache.display()
+    ))
+}
+
+/// The metadata libalpm has about `pkg`, with the install date and size of `local` when it is
+/// installed.
+pub(super) fn alpm_metadata(pkg: &Pkg, local: Option<&Pkg>) -> PackageData {
+    let size = |size: i64| u64::try_from(size).ok().filter(|&size| size > 0);
+    PackageData {
+        description: pkg.desc().map(str::to_string),
+        installed_size: size(local.unwrap_or(pkg).isize()),
+        // Only known to the sync databases
+        download_size: size(pkg.size()),
+        licenses: pkg.licenses().iter().map(str::to_string).collect(),
+        url: pkg.url().map(str::to_string),
+        packager: pkg.packager().map(str::to_string),
+        install_date: local.and_then(|local| local.install_date()),
+        ..Default::default()
+    }
+}
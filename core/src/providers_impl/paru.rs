@@ -0,0 +1,252 @@
+use alpm::Alpm;
+use flate2::read::GzDecoder;
+use secstr::SecVec;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{BufReader, Read},
+    ops::Sub,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    command::{Cmd, CommandStream},
+    error::Result,
+    executor::{self, Executor, SharedExecutor},
+    package::{PackageData, PackageStatus},
+    privilege::Privilege,
+    progress::ProgressFormat,
+    provider::{split_changes, Change, ProviderActions},
+    utils,
+};
+#[derive(Clone, Debug)]
+pub struct Paru {
+    pub name: String,
+    pub packages: Vec<PackageData>,
+    pub installed: usize,
+    pub total: usize,
+    pub root_required: bool,
+    executor: SharedExecutor,
+}
+
+impl Default for Paru {
+    fn default() -> Self {
+        Paru {
+            name: String::from("Paru"),
+            packages: Vec::new(),
+            root_required: true,
+            executor: executor::system(),
+            installed: 0,
+            total: 0,
+        }
+    }
+}
+
+impl Paru {
+    pub fn with_executor(executor: SharedExecutor) -> Self {
+        Paru {
+            executor,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct AurPackageShort {
+    name: String,
+    version: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default, rename = "URL")]
+    url: Option<String>,
+    #[serde(default)]
+    popularity: Option<f64>,
+}
+
+impl ProviderActions for Paru {
+    fn installed(&self) -> usize {
+        self.installed
+    }
+    fn total(&self) -> usize {
+        self.total
+    }
+    fn is_root_required(&self) -> bool {
+        self.root_required
+    }
+    fn progress_format(&self) -> ProgressFormat {
+        ProgressFormat::Pacman
+    }
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    fn packages(&self) -> Vec<PackageData> {
+        self.packages.clone()
+    }
+    fn load_packages(&mut self) -> Result<()> {
+        self.packages.clear();
+
+        let handle = Alpm::new("/", "/var/lib/pacman")?;
+
+        self.packages = get_json_packages(self.executor.as_ref())?
+            .iter()
+            .map(|pkg| {
+                let local = handle.localdb().pkg(pkg.name.to_string()).ok();
+                let installed_version = local.map(|local| local.version().to_string());
+                // The AUR knows the description even when the package isn't installed
+                let metadata = match local {
+                    Some(local) => super::pacman::alpm_metadata(local, Some(local.as_ref())),
+                    None => PackageData::default(),
+                };
+                PackageData {
+                    status: PackageStatus::from_versions(
+                        installed_version.as_deref(),
+                        &pkg.version,
+                    ),
+                    repository: "AUR".to_string(),
+                    name: pkg.name.to_string(),
+                    qualified_name: pkg.name.to_string(),
+                    installed_version,
+                    available_version: Some(pkg.version.to_string()),
+                    description: pkg.description.clone().or(metadata.description),
+                    url: pkg.url.clone().or(metadata.url),
+                    popularity: pkg.popularity,
+                    ..metadata
+                }
+            })
+            .collect();
+
+        self.installed = handle.localdb().pkgs().len();
+        self.total = self.packages.len();
+        Ok(())
+    }
+    fn package_info(&self, package: String) -> Result<String> {
+        self.executor
+            .run(&Cmd::new("paru").arg("-Si").packages([package])?)
+    }
+    fn install(&self, password: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        self.executor.stream_pty(
+            with_validation(vec![paru(&["-Syu"], [package])?]),
+            Privilege::current().input(password)?,
+        )
+    }
+    fn remove(&self, password: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        self.executor.stream_pty(
+            with_validation(vec![paru(&["-Runs"], [package])?]),
+            Privilege::current().input(password)?,
+        )
+    }
+    fn update(&self, password: Option<SecVec<u8>>) -> Result<CommandStream> {
+        self.executor.stream_pty(
+            paru(&["-Syu"], Vec::<String>::new())?,
+            Privilege::current().input(password)?,
+        )
+    }
+    fn execute(&self, password: Option<SecVec<u8>>, changes: Vec<Change>) -> Result<CommandStream> {
+        let (install, remove) = split_changes(&changes);
+        let mut commands = Vec::new();
+        if !install.is_empty() {
+            commands.push(paru(&["-Syu"], install)?);
+        }
+        if !remove.is_empty() {
+            commands.push(paru(&["-Runs"], remove)?);
+        }
+        self.executor.stream_pty(
+            with_validation(commands),
+            Privilege::current().input(password)?,
+        )
+    }
+    fn update_selected(
+        &self,
+        password: Option<SecVec<u8>>,
+        packages: Vec<String>,
+    ) -> Result<CommandStream> {
+        self.executor.stream_pty(
+            with_validation(vec![paru(&["-S", "--needed"], packages)?]),
+            Privilege::current().input(password)?,
+        )
+    }
+    fn upgradable(&self) -> Result<Vec<PackageData>> {
+        // Like pacman -Qu, paru exits with 1 when there is nothing to upgrade
+        let output = self
+            .executor
+            .run_with_codes(&Cmd::new("paru").arg("-Qua"), &[0, 1])?;
+        Ok(utils::parse_upgrade_list(&output)
+            .into_iter()
+            .map(|(name, old, new)| PackageData {
+                status: PackageStatus::UpdateAvailable,
+                repository: "AUR".to_string(),
+                qualified_name: name.clone(),
+                name,
+                installed_version: Some(old),
+                available_version: Some(new),
+                ..Default::default()
+            })
+            .collect())
+    }
+    fn is_available(&self) -> bool {
+        let packages = self.executor.run(&Cmd::new("paru").arg("--version"));
+        packages.is_ok()
+    }
+}
+
+fn json_path() -> anyhow::Result<PathBuf> {
+    let mut path = utils::system_path()?;
+    path.push("aur_packages.json");
+    Ok(path)
+}
+
+fn get_json_packages(executor: &dyn Executor) -> anyhow::Result<Vec<AurPackageShort>> {
+    let path = json_path()?;
+    let exists = fs::exists(&path)?;
+    if exists {
+        let created = fs::File::open(&path)?.metadata()?.created()?;
+        let yesterday = SystemTime::now().sub(Duration::from_hours(24));
+
+        if created > yesterday {
+            let file = fs::File::open(&path)?;
+            let reader = BufReader::new(file);
+            return Ok(serde_json::from_reader(reader)?);
+        }
+    }
+    download_json(executor)
+}
+
+fn download_json(executor: &dyn Executor) -> anyhow::Result<Vec<AurPackageShort>> {
+    let response = executor.fetch("https://aur.archlinux.org/packages-meta-v1.json.gz")?;
+
+    let mut decoder = GzDecoder::new(response.as_slice());
+    let mut json_string = String::new();
+    decoder.read_to_string(&mut json_string)?;
+
+    let list = serde_json::from_str::<Vec<AurPackageShort>>(&json_string)?;
+
+    let path = json_path()?;
+    let file = fs::File::create(path)?;
+    serde_json::to_writer(file, &list)?;
+
+    Ok(list)
+}
+
+/// paru runs pacman through sudo itself, told here how to elevate.
+fn paru<I, S>(operation: &[&str], packages: I) -> anyhow::Result<Cmd>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    Ok(Cmd::new("paru")
+        .args(operation)
+        .packages(packages)?
+        .args(Privilege::current().sudo_flags()))
+}
+
+/// Asks for the credentials before paru starts building, chaining `commands`.
+fn with_validation(commands: Vec<Cmd>) -> Cmd {
+    let commands: Vec<Cmd> = Privilege::current()
+        .validate()
+        .into_iter()
+        .chain(commands)
+        .collect();
+    Cmd::chain(&commands)
+}
@@ -0,0 +1,360 @@
+use crate::command::{Cmd, CommandStream};
+use crate::executor::{self, SharedExecutor};
+use crate::{
+    api,
+    details::PackageDetails,
+    error::{ProviderError, Result},
+    package::{PackageData, PackageStatus},
+    progress::ProgressFormat,
+    provider::{split_changes, Change, ProviderActions},
+};
+use anyhow::{anyhow, Context};
+use rayon::prelude::*;
+use regex::Regex;
+use secstr::SecVec;
+use serde::Deserialize;
+use std::env;
+use std::fs::{self, DirEntry};
+use std::path::Path;
+
+/// Suffix of the folders of releases built for one architecture.
+const ARCH_SUFFIX: &str = r"(-aarch64)|(-x86_64)";
+
+#[derive(Clone, Debug)]
+pub struct ProtonGE {
+    name: String,
+    packages: Vec<PackageData>,
+    installed: usize,
+    total: usize,
+    root_required: bool,
+    executor: SharedExecutor,
+    endpoint: String,
+    folder_path: String,
+    packages_description: Vec<ApiResponse>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+struct ApiResponse {
+    tag_name: String,
+    body: String,
+    html_url: String,
+    assets_url: String
+}
+
+#[derive(Deserialize, Debug)]
+struct ApiAssets {
+    browser_download_url: String,
+    name: String,
+}
+
+impl Default for ProtonGE {
+    fn default() -> Self {
+        let home = env::var("HOME").unwrap_or_default();
+        let mut folder_path: &str = "";
+        if Path::new(&format!("{}{}", home, "/.steam")).exists() {
+            folder_path = "/.steam/root/compatibilitytools.d";
+        }
+        if Path::new(&format!("{}{}", home, "/.var/app/com.valvesoftware.Steam")).exists() {
+            folder_path = "/.var/app/com.valvesoftware.Steam/data/Steam/compatibilitytools.d";
+        }
+        ProtonGE {
+            name: String::from("Proton GE"),
+            packages: Vec::new(),
+            root_required: false,
+            executor: executor::system(),
+            installed: 0,
+            total: 0,
+            endpoint: String::from(
+                "https://api.github.com/repos/GloriousEggroll/proton-ge-custom/releases",
+            ),
+            folder_path: folder_path.to_string(),
+            packages_description: Vec::new(),
+        }
+    }
+}
+
+impl ProviderActions for ProtonGE {
+    fn load_packages(&mut self) -> Result<()> {
+        let provider = Self::new(self.executor.clone())?;
+        self.packages_description = provider.packages_description;
+        self.packages = provider.packages;
+        self.installed = provider.installed;
+        self.total = provider.total;
+        Ok(())
+    }
+    fn name(&self) -> String {
+        self.name.to_owned()
+    }
+    fn is_root_required(&self) -> bool {
+        self.root_required
+    }
+    fn progress_format(&self) -> ProgressFormat {
+        ProgressFormat::Wget
+    }
+    fn packages(&self) -> Vec<PackageData> {
+        self.packages.clone()
+    }
+    fn package_info(&self, package: String) -> Result<String> {
+        let value = self.api_package_data(&package)?;
+        Ok(format!("URL: {}\n{}", value.html_url, value.body))
+    }
+    fn package_details(&self, package: String) -> Result<PackageDetails> {
+        let raw = self.package_info(package.clone())?;
+        let value = self.api_package_data(&package)?;
+        Ok(PackageDetails {
+            fields: vec![("Version".to_string(), value.tag_name.clone())],
+            links: vec![("Release".to_string(), value.html_url.clone())],
+            release_notes: Some(value.body.clone()).filter(|body| !body.is_empty()),
+            raw,
+            ..Default::default()
+        })
+    }
+    fn install(&self, _: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        self.download(&package)
+    }
+    fn remove(&self, _password: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        let commands = [
+            self.remove_command(&package)?,
+            Cmd::new("echo").arg("Removed."),
+        ];
+        self.executor.stream(Cmd::chain(&commands), None)
+    }
+    fn execute(&self, _: Option<SecVec<u8>>, changes: Vec<Change>) -> Result<CommandStream> {
+        let (install, remove) = split_changes(&changes);
+        let mut commands = remove
+            .iter()
+            .map(|package| self.remove_command(package))
+            .collect::<anyhow::Result<Vec<Cmd>>>()?;
+        commands.push(Cmd::new("echo").arg(&format!("Removed {} package(s).", remove.len())));
+        let proton_location = self.proton_location()?;
+        for package in &install {
+            let url = self.download_url(package)?;
+            commands.push(api::download_and_extract_command(&url, &proton_location));
+        }
+        self.executor.stream(Cmd::chain(&commands), None)
+    }
+    fn update(&self, _: Option<SecVec<u8>>) -> Result<CommandStream> {
+        if self.packages.is_empty() {
+            return standalone_upate();
+        }
+
+        if !self.packages[0].installed() {
+            self.download(&self.packages[0].name)
+        } else {
+            self.executor
+                .stream(Cmd::new("echo").arg("Nothing to do."), None)
+        }
+    }
+    fn update_selected(
+        &self,
+        _: Option<SecVec<u8>>,
+        packages: Vec<String>,
+    ) -> Result<CommandStream> {
+        match self.upgradable()?.first() {
+            Some(latest) if packages.contains(&latest.name) => self.download(&latest.name),
+            _ => self
+                .executor
+                .stream(Cmd::new("echo").arg("Nothing to do."), None),
+        }
+    }
+    fn upgradable(&self) -> Result<Vec<PackageData>> {
+        let latest = match self.packages.first() {
+            Some(latest) if !latest.installed() => latest,
+            _ => return Ok(Vec::new()),
+        };
+        let installed_version = self
+            .packages
+            .iter()
+            .find(|package| package.installed())
+            .and_then(|package| package.installed_version.clone());
+        if installed_version.is_none() {
+            return Ok(Vec::new());
+        }
+        Ok(vec![PackageData {
+            status: PackageStatus::UpdateAvailable,
+            installed_version,
+            ..latest.clone()
+        }])
+    }
+    fn installed(&self) -> usize {
+        self.installed
+    }
+    fn total(&self) -> usize {
+        self.total
+    }
+    fn is_available(&self) -> bool {
+        if cfg!(windows) {
+            return false;
+        }
+        let home = env::var("HOME").unwrap_or_default();
+        // GitHub is only asked when loading, so the cached releases can be shown offline
+        Path::new(&format!("{}{}", home, "/.steam")).exists()
+            || Path::new(&format!("{}{}", home, "/.var/app/com.valvesoftware.Steam")).exists()
+    }
+}
+impl ProtonGE {
+    pub fn with_executor(executor: SharedExecutor) -> Self {
+        ProtonGE {
+            executor,
+            ..Default::default()
+        }
+    }
+    fn new(executor: SharedExecutor) -> anyhow::Result<Self> {
+        let regex_arch = Regex::new(ARCH_SUFFIX).expect("Invalid regex");
+        let mut protonge = ProtonGE::with_executor(executor);
+        let proton_location = protonge.proton_location()?;
+        let proton_dir = fs::read_dir(proton_location)?;
+        let proton: Vec<String> = proton_dir.filter_map(|dir| {
+            if let Ok(name) = filter_dir(dir) {
+                Some(regex_arch.replace(&name, "").to_string())
+            } else {
+                None
+            }
+        }).collect();
+        let resp = api::parse::<Vec<ApiResponse>>(&protonge.executor.fetch(&protonge.endpoint)?)?;
+        protonge.packages_description = resp;
+        protonge.packages = protonge
+            .packages_description
+            .par_iter()
+            .map(|package| {
+                let name = &package.tag_name;
+                let name = if name.contains("GE-Proton") {
+                    package.tag_name.to_owned()
+                } else {
+                    format!("GE-Proton{name}")
+                };
+                let version = name[9..].to_string();
+                let installed = proton.contains(&name);
+                PackageData {
+                    status: if installed {
+                        PackageStatus::UpToDate
+                    } else {
+                        PackageStatus::NotInstalled
+                    },
+                    name: name.to_owned(),
+                    qualified_name: name.to_owned(),
+                    repository: "GloriousEggroll".to_owned(),
+                    installed_version: installed.then(|| version.clone()),
+                    available_version: Some(version),
+                    url: Some(package.html_url.clone()),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        // The API only lists the latest releases, older installed ones show up as foreign
+        let foreign: Vec<PackageData> = proton
+            .iter()
+            .filter(|name| name.starts_with("GE-Proton"))
+            .filter(|name| {
+                !protonge
+                    .packages
+                    .iter()
+                    .any(|package| package.name.eq(*name))
+            })
+            .map(|name| PackageData {
+                status: PackageStatus::Foreign,
+                name: name.to_owned(),
+                qualified_name: name.to_owned(),
+                repository: "GloriousEggroll".to_owned(),
+                installed_version: Some(name[9..].to_string()),
+                available_version: None,
+                ..Default::default()
+            })
+            .collect();
+        protonge.packages.extend(foreign);
+        protonge.installed = protonge
+            .packages
+            .par_iter()
+            .filter(|&p| p.installed())
+            .count();
+        protonge.total = protonge.packages.len();
+        Ok(protonge)
+    }
+    fn api_package_data(&self, tag_name: &str) -> Result<&ApiResponse> {
+        self.packages_description
+            .par_iter()
+            .find_any(|response| response.tag_name.eq(tag_name))
+            .ok_or_else(|| ProviderError::PackageNotFound(tag_name.to_string()))
+    }
+    fn package(&self, name: &str) -> Result<&PackageData> {
+        self.packages
+            .par_iter()
+            .find_any(|package| package.name.eq(name))
+            .ok_or_else(|| ProviderError::PackageNotFound(name.to_string()))
+    }
+    fn download(&self, package: &str) -> Result<CommandStream> {
+        let command = api::download_and_extract_command(
+            &self.download_url(package)?,
+            &self.proton_location()?,
+        );
+        self.executor.stream(command, None)
+    }
+    fn download_url(&self, package: &str) -> anyhow::Result<String> {
+        let arch = if std::env::consts::ARCH == "x86_64" {
+            "aarch64"
+        } else {
+            "x86_64"
+        };
+        let mut url = Err(anyhow!("URL not found"));
+        let api_response = self.api_package_data(package)?;
+        let assets = self
+            .executor
+            .fetch(&api_response.assets_url)
+            .map_err(anyhow::Error::from)
+            .and_then(|body| api::parse::<Vec<ApiAssets>>(&body));
+        if let Ok(assets) = assets {
+            for file in assets {
+                if file.name.contains(".tar.gz") && !file.name.contains(arch) {
+                    url = Ok(file.browser_download_url);
+                    break;
+                }
+            }
+        }
+
+        url
+    }
+    /// Removes the folders of `package`, as a command so it runs on the command page.
+    fn remove_command(&self, package: &str) -> anyhow::Result<Cmd> {
+        let pkg = self.package(package)?;
+        let regex_arch = Regex::new(ARCH_SUFFIX).expect("Invalid regex");
+        let proton_location = self.proton_location()?;
+        let folders: Vec<String> = fs::read_dir(&proton_location)?
+            .filter_map(|dir| filter_dir(dir).ok())
+            .filter(|name| regex_arch.replace(name, "") == pkg.name)
+            .map(|name| format!("{proton_location}/{name}"))
+            .collect();
+        if folders.is_empty() {
+            return Err(ProviderError::PackageNotFound(package.to_string()).into());
+        }
+
+        Ok(Cmd::new("rm").args(["-rf", "--"]).args(folders))
+    }
+    fn proton_location(&self) -> anyhow::Result<String> {
+        let home = env::var("HOME")?;
+        let path = format!("{}{}", home, &self.folder_path);
+        if !Path::new(&path).exists() {
+            let _ = fs::create_dir_all(format!("{}{}", home, &self.folder_path));
+        }
+        Ok(path)
+    }
+}
+
+fn filter_dir(dir: std::io::Result<DirEntry>) -> anyhow::Result<String> {
+    let entry = dir?;
+    let file_type = entry.file_type()?;
+    if file_type.is_dir() {
+        let name = entry
+            .file_name()
+            .to_str()
+            .context("Failed to get folder name")?
+            .to_owned();
+        Ok(name)
+    } else {
+        Err(anyhow!("Not a Folder"))
+    }
+}
+
+fn standalone_upate() -> Result<CommandStream> {
+    let instance = ProtonGE::new(executor::system())?;
+    instance.update(None)
+}
@@ -0,0 +1,176 @@
+use std::cmp::Ordering;
+
+use rayon::prelude::*;
+use regex::Regex;
+use secstr::SecVec;
+
+use crate::{
+    ansi,
+    command::{Cmd, CommandStream},
+    error::Result,
+    executor::{self, SharedExecutor},
+    package::{PackageData, PackageStatus},
+    provider::{split_changes, Change, ProviderActions},
+    utils,
+};
+
+#[derive(Clone, Debug)]
+pub struct Soar {
+    name: String,
+    packages: Vec<PackageData>,
+    installed: usize,
+    total: usize,
+    root_required: bool,
+    executor: SharedExecutor,
+}
+
+impl Default for Soar {
+    fn default() -> Self {
+        Soar {
+            name: String::from("Soar"),
+            packages: Vec::new(),
+            root_required: false,
+            executor: executor::system(),
+            installed: 0,
+            total: 0,
+        }
+    }
+}
+
+impl Soar {
+    pub fn with_executor(executor: SharedExecutor) -> Self {
+        Soar {
+            executor,
+            ..Default::default()
+        }
+    }
+}
+
+impl ProviderActions for Soar {
+    fn installed(&self) -> usize {
+        self.installed
+    }
+    fn total(&self) -> usize {
+        self.total
+    }
+    fn is_root_required(&self) -> bool {
+        self.root_required
+    }
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    fn packages(&self) -> Vec<PackageData> {
+        self.packages.clone()
+    }
+    fn load_packages(&mut self) -> Result<()> {
+        self.packages.clear();
+
+        let regex_installed = Regex::new(r"\[[✓?○]\]").expect("Invalid regex");
+
+        let packages = self.executor.run(&Cmd::new("soar").arg("list"))?;
+        let packages: Vec<&str> = packages.split('\n').collect();
+
+        self.packages = packages
+            .iter()
+            .filter_map(|_package| {
+                let package = ansi::strip(_package);
+                let list_package: Vec<&str> = package.split(" | ").collect();
+                if list_package.len() == 3 && regex_installed.is_match(list_package[0]) {
+                    let version = list_package[1].trim().to_string();
+                    let installed = list_package[0].contains("[✓]");
+                    let qualified_name = regex_installed
+                        .replace_all(list_package[0], "")
+                        .trim()
+                        .to_string();
+                    let name = qualified_name.split("#").collect::<Vec<&str>>()[0].to_string();
+
+                    return Some(PackageData {
+                        status: if installed {
+                            PackageStatus::UpToDate
+                        } else {
+                            PackageStatus::NotInstalled
+                        },
+                        repository: list_package[2].trim().to_string(),
+                        installed_version: installed.then(|| version.clone()),
+                        available_version: Some(version),
+                        qualified_name,
+                        name,
+                        ..Default::default()
+                    });
+                }
+                None
+            })
+            .collect::<Vec<PackageData>>();
+
+        self.installed = self.packages.par_iter().filter(|&p| p.installed()).count();
+        self.total = self.packages.len();
+        Ok(())
+    }
+    fn package_info(&self, package: String) -> Result<String> {
+        let result = self
+            .executor
+            .run(&Cmd::new("soar").arg("query").packages([package])?)?;
+        Ok(ansi::strip(&result))
+    }
+    fn install(&self, _: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        self.executor
+            .stream(Cmd::new("soar").arg("install").packages([package])?, None)
+    }
+    fn remove(&self, _: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        self.executor
+            .stream(Cmd::new("soar").arg("remove").packages([package])?, None)
+    }
+    fn update(&self, _: Option<SecVec<u8>>) -> Result<CommandStream> {
+        self.executor.stream(Cmd::new("soar").arg("update"), None)
+    }
+    fn execute(&self, _: Option<SecVec<u8>>, changes: Vec<Change>) -> Result<CommandStream> {
+        let (install, remove) = split_changes(&changes);
+        let mut commands = Vec::new();
+        if !install.is_empty() {
+            commands.push(Cmd::new("soar").arg("install").packages(install)?);
+        }
+        if !remove.is_empty() {
+            commands.push(Cmd::new("soar").arg("remove").packages(remove)?);
+        }
+        self.executor.stream(Cmd::chain(&commands), None)
+    }
+    fn update_selected(
+        &self,
+        _: Option<SecVec<u8>>,
+        packages: Vec<String>,
+    ) -> Result<CommandStream> {
+        self.executor
+            .stream(Cmd::new("soar").arg("update").packages(packages)?, None)
+    }
+    fn upgradable(&self) -> Result<Vec<PackageData>> {
+        // `soar list` only shows the version of the repository, `soar info` the installed one,
+        // e.g. `bat#github.com.sharkdp.bat-0.23.0:bincache (2024-05-01 10:00) (5.1 MiB)`
+        let regex_installed = Regex::new(r"^(\S+?)#(\S+)-([^-\s]+):(\S+)").expect("Invalid regex");
+        let output = self.executor.run(&Cmd::new("soar").arg("info"))?;
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let line = ansi::strip(line);
+                let captures = regex_installed.captures(line.trim())?;
+                let qualified_name = format!("{}#{}:{}", &captures[1], &captures[2], &captures[4]);
+                let installed_version = &captures[3];
+                let package = self
+                    .packages
+                    .iter()
+                    .find(|package| package.qualified_name == qualified_name)?;
+                let available = package.available_version.as_deref()?;
+                (utils::version_cmp(available, installed_version) == Ordering::Greater).then(|| {
+                    PackageData {
+                        status: PackageStatus::UpdateAvailable,
+                        installed_version: Some(installed_version.to_string()),
+                        ..package.clone()
+                    }
+                })
+            })
+            .collect())
+    }
+    fn is_available(&self) -> bool {
+        let result = self.executor.run(&Cmd::new("soar").arg("--version"));
+        result.is_ok()
+    }
+}
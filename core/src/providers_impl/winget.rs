@@ -0,0 +1,401 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use rayon::prelude::*;
+use rusqlite::Connection;
+use secstr::SecVec;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command::{self, Cmd, CommandStream},
+    error::Result,
+    executor::{self, Executor, SharedExecutor},
+    package::{PackageData, PackageStatus},
+    provider::{Change, Operation, ProviderActions},
+    utils,
+};
+
+const INDEX_URL: &str = "https://cdn.winget.microsoft.com/cache/source.msix";
+
+#[derive(Clone, Debug)]
+pub struct Winget {
+    name: String,
+    packages: Vec<PackageData>,
+    installed: usize,
+    total: usize,
+    root_required: bool,
+    executor: SharedExecutor,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WingetJson {
+    #[serde(rename = "Sources")]
+    sources: Vec<SourceJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SourceJson {
+    #[serde(rename = "Packages")]
+    packages: Vec<PackageJson>,
+    #[serde(rename = "SourceDetails")]
+    source_details: SourceDetailsJson,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SourceDetailsJson {
+    #[serde(rename = "Argument")]
+    argument: String,
+    #[serde(rename = "Identifier")]
+    identifier: String,
+    #[serde(rename = "Name")]
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PackageJson {
+    #[serde(rename = "PackageIdentifier")]
+    package_identifier: String,
+    #[serde(rename = "Version")]
+    version: String,
+}
+
+impl Default for Winget {
+    fn default() -> Self {
+        Winget {
+            name: String::from("Winget"),
+            packages: Vec::new(),
+            root_required: false,
+            executor: executor::system(),
+            installed: 0,
+            total: 0,
+        }
+    }
+}
+
+impl Winget {
+    pub fn with_executor(executor: SharedExecutor) -> Self {
+        Winget {
+            executor,
+            ..Default::default()
+        }
+    }
+}
+
+impl ProviderActions for Winget {
+    fn installed(&self) -> usize {
+        self.installed
+    }
+    fn total(&self) -> usize {
+        self.total
+    }
+    fn is_root_required(&self) -> bool {
+        self.root_required
+    }
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    fn packages(&self) -> Vec<PackageData> {
+        self.packages.clone()
+    }
+    fn load_packages(&mut self) -> Result<()> {
+        self.packages.clear();
+
+        let mut path = utils::system_path()?;
+        path.push("winget_installed.json");
+
+        self.executor.run(
+            &Cmd::new("winget")
+                .args(["export", "--include-versions", "--nowarn", "-o"])
+                .arg(path.to_str().context("Unable to get path")?),
+        )?;
+        let file = utils::open_file(path)?;
+        let reader = BufReader::new(file);
+        let winget_json: WingetJson = serde_json::from_reader(reader)?;
+
+        let mut installed_packages: Vec<PackageData> = Vec::new();
+        for source in winget_json.sources {
+            let mut packages: Vec<PackageData> = source
+                .packages
+                .par_iter()
+                .map(|pkg| PackageData {
+                    status: PackageStatus::Foreign,
+                    name: "".to_string(),
+                    repository: source.source_details.name.clone(),
+                    qualified_name: pkg.package_identifier.clone(),
+                    installed_version: Some(pkg.version.clone()),
+                    available_version: None,
+                    ..Default::default()
+                })
+                .collect();
+
+            installed_packages.append(&mut packages);
+        }
+
+        update_db(self.executor.as_ref(), &index_path()?)?;
+        self.packages = list_db(&connection()?, &installed_packages)?;
+        // Installed packages missing from the winget index (e.g. msstore) are foreign
+        let indexed: HashSet<&str> = self
+            .packages
+            .par_iter()
+            .map(|package| package.qualified_name.as_str())
+            .collect();
+        let mut foreign: Vec<PackageData> = installed_packages
+            .par_iter()
+            .filter(|installed| !indexed.contains(installed.qualified_name.as_str()))
+            .cloned()
+            .collect();
+        self.packages.append(&mut foreign);
+
+        self.installed = installed_packages.len();
+        self.total = self.packages.len();
+        Ok(())
+    }
+    fn package_info(&self, package: String) -> Result<String> {
+        self.executor
+            .run(&Cmd::new("winget").arg("show").packages([package])?)
+    }
+    fn install(&self, _: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        self.executor.stream(install_command(&package)?, None)
+    }
+    fn remove(&self, _: Option<SecVec<u8>>, package: String) -> Result<CommandStream> {
+        self.executor.stream(uninstall_command(&package)?, None)
+    }
+    fn update(&self, _: Option<SecVec<u8>>) -> Result<CommandStream> {
+        self.executor
+            .stream(Cmd::new("winget").args(["upgrade", "-h", "--all"]), None)
+    }
+    fn execute(&self, _: Option<SecVec<u8>>, changes: Vec<Change>) -> Result<CommandStream> {
+        // winget handles a single package per call
+        let commands = changes
+            .iter()
+            .map(|change| match change.operation {
+                Operation::Install => install_command(&change.package),
+                Operation::Remove => uninstall_command(&change.package),
+            })
+            .collect::<anyhow::Result<Vec<Cmd>>>()?;
+        self.executor.stream(Cmd::chain(&commands), None)
+    }
+    fn update_selected(
+        &self,
+        _: Option<SecVec<u8>>,
+        packages: Vec<String>,
+    ) -> Result<CommandStream> {
+        let commands = packages
+            .iter()
+            .map(|package| {
+                Cmd::new("winget")
+                    .args(["upgrade", "-h", "-e", "--id"])
+                    .packages([package])
+            })
+            .collect::<anyhow::Result<Vec<Cmd>>>()?;
+        self.executor.stream(Cmd::chain(&commands), None)
+    }
+    fn is_available(&self) -> bool {
+        let packages = self.executor.run(&Cmd::new("winget").arg("--version"));
+        packages.is_ok()
+    }
+}
+
+fn install_command(package: &str) -> anyhow::Result<Cmd> {
+    Cmd::new("winget")
+        .args(["install", "-e", "--id"])
+        .packages([package])
+}
+
+/// Installed packages are uninstalled by their display name, which may contain spaces.
+fn uninstall_command(package: &str) -> anyhow::Result<Cmd> {
+    let pkg = get_package(package)?;
+    Ok(Cmd::new("winget")
+        .arg("uninstall")
+        .arg(command::name_argument(&pkg.name)?))
+}
+
+fn update_db(executor: &dyn Executor, path: &Path) -> anyhow::Result<()> {
+    let bytes = executor.fetch(INDEX_URL)?;
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(bytes))?;
+
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        let name = file.name();
+
+        if name == "Public/index.db" {
+            let mut out_file = std::fs::File::create(path)?;
+            std::io::copy(&mut file, &mut out_file)?;
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn index_path() -> anyhow::Result<PathBuf> {
+    let mut path = utils::system_path()?;
+    path.push("index.db");
+    Ok(path)
+}
+
+fn connection() -> anyhow::Result<Connection> {
+    let path = index_path()?;
+    let conn = Connection::open(path.to_str().context("Unable to get path")?)?;
+    Ok(conn)
+}
+
+fn get_package(id: &str) -> anyhow::Result<PackageData> {
+    let conn = connection()?;
+
+    let obj = conn.query_row(
+        "
+            SELECT ids.id, names.name, versions.version
+            FROM manifest
+            INNER JOIN names
+                ON manifest.name = names.rowid
+            INNER JOIN ids
+                ON manifest.id = ids.rowid
+            INNER JOIN versions
+                ON versions.rowid = manifest.version
+            WHERE ids.id = ?
+            GROUP BY ids.id
+            HAVING MAX(manifest.version) = manifest.version",
+        [id],
+        |row| {
+            let qualified_name: String = row.get(0)?;
+            let name = row.get(1)?;
+            let version = row.get(2)?;
+
+            let res = PackageData {
+                status: PackageStatus::NotInstalled,
+                repository: "winget".to_string(),
+                qualified_name: qualified_name.clone(),
+                name,
+                installed_version: None,
+                available_version: Some(version),
+                ..Default::default()
+            };
+
+            Ok(res)
+        },
+    )?;
+
+    Ok(obj)
+}
+
+fn list_db(
+    conn: &Connection,
+    installed_packages: &[PackageData],
+) -> anyhow::Result<Vec<PackageData>> {
+    let installed_versions: HashMap<&str, &str> = installed_packages
+        .iter()
+        .filter_map(|f| Some((f.qualified_name.as_str(), f.installed_version.as_deref()?)))
+        .collect();
+
+    let mut stmt = conn.prepare(
+        "
+            SELECT ids.id, names.name, versions.version
+            FROM manifest
+            INNER JOIN names
+                ON manifest.name = names.rowid
+            INNER JOIN ids
+                ON manifest.id = ids.rowid
+            INNER JOIN versions
+                ON versions.rowid = manifest.version
+            GROUP BY ids.id
+            HAVING MAX(manifest.version) = manifest.version",
+    )?;
+    let result = stmt
+        .query_map([], |row| {
+            let qualified_name: String = row.get(0)?;
+            let name = row.get(1)?;
+            let version: String = row.get(2)?;
+            let installed_version = installed_versions
+                .get(qualified_name.as_str())
+                .map(|version| version.to_string());
+
+            Ok(PackageData {
+                status: PackageStatus::from_versions(installed_version.as_deref(), &version),
+                repository: "winget".to_string(),
+                qualified_name: qualified_name.clone(),
+                name,
+                installed_version,
+                available_version: Some(version),
+                ..Default::default()
+            })
+        })?
+        .map(|result| result.map_err(anyhow::Error::new))
+        .collect();
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io::Write};
+
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use super::*;
+    use crate::executor::Replay;
+
+    /// The msix the index is published in, holding the database built by `index.sql`.
+    fn index_archive(dir: &Path) -> Vec<u8> {
+        let database = dir.join("source.db");
+        Connection::open(&database)
+            .unwrap()
+            .execute_batch(include_str!("../../tests/fixtures/winget/index.sql"))
+            .unwrap();
+        let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        zip.start_file("Public/index.db", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(&fs::read(&database).unwrap()).unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn winget_lists_the_latest_versions_of_the_downloaded_index() {
+        let dir = std::env::temp_dir().join(format!("winget-index-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let replay = Replay::new().record_download(INDEX_URL, index_archive(&dir));
+        let index = dir.join("index.db");
+        update_db(&replay, &index).unwrap();
+
+        let installed = [PackageData {
+            status: PackageStatus::Foreign,
+            qualified_name: "Git.Git".to_string(),
+            installed_version: Some("2.44.0".to_string()),
+            ..Default::default()
+        }];
+        let packages = list_db(&Connection::open(&index).unwrap(), &installed).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let summary: Vec<_> = packages
+            .iter()
+            .map(|package| {
+                (
+                    package.qualified_name.as_str(),
+                    package.name.as_str(),
+                    package.status,
+                    package.available_version.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "Git.Git",
+                    "Git",
+                    PackageStatus::UpdateAvailable,
+                    Some("2.45.0")
+                ),
+                (
+                    "Mozilla.Firefox",
+                    "Mozilla Firefox",
+                    PackageStatus::NotInstalled,
+                    Some("125.0.1")
+                ),
+            ]
+        );
+    }
+}
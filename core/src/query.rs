@@ -0,0 +1,269 @@
+//! The search syntax of the package list, e.g. `repo:extra installed:yes -name:"lib" editor`.
+//!
+//! Words without a field match the name, the qualified name or the description, `-` excludes what a term matches and quotes
+//! keep spaces in a value. Every term has to match.
+
+use std::{cmp::Ordering, fmt};
+
+use crate::{
+    package::{PackageData, PackageStatus},
+    utils,
+};
+
+/// Fields a term can be restricted to, as they are written before the `:`.
+pub const FIELDS: [&str; 7] = [
+    "name",
+    "description",
+    "repo",
+    "provider",
+    "installed",
+    "updates",
+    "version",
+];
+
+/// A parsed search, matching the packages that satisfy all its terms.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    negated: bool,
+    filter: Filter,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    /// Text matched against the name, the qualified name and the description.
+    Text(String),
+    Name(String),
+    Description(String),
+    Repository(String),
+    Provider(String),
+    Installed(bool),
+    Updates(bool),
+    /// Without a comparison the version only has to contain the text.
+    Version(Option<Ordering>, bool, String),
+}
+
+/// Why a search couldn't be parsed, with the character it was noticed at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at character {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Self, QueryError> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut terms = Vec::new();
+        let mut index = 0;
+        while index < chars.len() {
+            if chars[index].is_whitespace() {
+                index += 1;
+                continue;
+            }
+            let start = index;
+            let negated = chars[index] == '-';
+            if negated {
+                index += 1;
+            }
+            let (word, quoted, end) = read_value(&chars, index)?;
+            index = end;
+
+            // `field:value`, where the value may be quoted
+            let field = (!quoted)
+                .then(|| word.split_once(':'))
+                .flatten()
+                .filter(|(field, _)| field.chars().all(char::is_alphabetic))
+                .map(|(field, value)| (field.to_lowercase(), value.to_string()));
+            let filter = match field {
+                Some((field, value)) => {
+                    let value_start = start + usize::from(negated) + field.chars().count() + 1;
+                    let value = if value.is_empty() && chars.get(index) == Some(&'"') {
+                        let (value, _, end) = read_value(&chars, index)?;
+                        index = end;
+                        value
+                    } else {
+                        value
+                    };
+                    parse_filter(&field, value, start, value_start)?
+                }
+                None if word.is_empty() && !quoted => {
+                    return Err(QueryError {
+                        position: start,
+                        message: "Expected a term after -".to_string(),
+                    });
+                }
+                None => Filter::Text(word),
+            };
+            terms.push(Term { negated, filter });
+        }
+        Ok(Query { terms })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// The text a package has to match, leaving out the excluded terms.
+    pub fn texts(&self) -> Vec<&str> {
+        self.terms
+            .iter()
+            .filter(|term| !term.negated)
+            .filter_map(|term| match &term.filter {
+                Filter::Text(text) | Filter::Name(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether the package of `provider` satisfies the query, `text_matches(value, text)` tells
+    /// whether a text term matches a name, so the caller can choose between exact and fuzzy.
+    /// Descriptions have to contain the text.
+    pub fn matches(
+        &self,
+        package: &PackageData,
+        provider: &str,
+        text_matches: impl Fn(&str, &str) -> bool,
+    ) -> bool {
+        self.terms.iter().all(|term| {
+            let matched = match &term.filter {
+                Filter::Text(text) => {
+                    text_matches(&package.qualified_name, text)
+                        || text_matches(&package.name, text)
+                        || describes(package, text)
+                }
+                Filter::Name(text) => text_matches(&package.name, text),
+                Filter::Description(text) => describes(package, text),
+                Filter::Repository(repository) => {
+                    package.repository.eq_ignore_ascii_case(repository)
+                }
+                Filter::Provider(name) => provider.eq_ignore_ascii_case(name),
+                Filter::Installed(installed) => package.installed() == *installed,
+                Filter::Updates(updates) => {
+                    (package.status == PackageStatus::UpdateAvailable) == *updates
+                }
+                Filter::Version(ordering, or_equal, version) => {
+                    let current = match package.installed() {
+                        true => package.installed_version.as_deref(),
+                        false => package.available_version.as_deref(),
+                    };
+                    current.is_some_and(|current| match ordering {
+                        None if !or_equal => {
+                            current.to_lowercase().contains(&version.to_lowercase())
+                        }
+                        None => utils::version_cmp(current, version) == Ordering::Equal,
+                        Some(ordering) => {
+                            let compared = utils::version_cmp(current, version);
+                            compared == *ordering || (*or_equal && compared == Ordering::Equal)
+                        }
+                    })
+                }
+            };
+            matched != term.negated
+        })
+    }
+}
+
+fn describes(package: &PackageData, text: &str) -> bool {
+    package
+        .description
+        .as_deref()
+        .is_some_and(|description| description.to_lowercase().contains(&text.to_lowercase()))
+}
+
+/// Reads a word or a quoted phrase starting at `index`, returning it, whether it was quoted and
+/// where it ends. A word stops before a quote, so `name:"a b"` reads `name:` then `a b`.
+fn read_value(chars: &[char], index: usize) -> Result<(String, bool, usize), QueryError> {
+    if chars.get(index) == Some(&'"') {
+        let Some(length) = chars[index + 1..].iter().position(|&c| c == '"') else {
+            return Err(QueryError {
+                position: index,
+                message: "Missing closing quote".to_string(),
+            });
+        };
+        let end = index + 1 + length;
+        let phrase = chars[index + 1..end].iter().collect();
+        return Ok((phrase, true, end + 1));
+    }
+    let length = chars[index..]
+        .iter()
+        .position(|&c| c.is_whitespace() || c == '"')
+        .unwrap_or(chars.len() - index);
+    let word = chars[index..index + length].iter().collect();
+    Ok((word, false, index + length))
+}
+
+fn parse_filter(
+    field: &str,
+    value: String,
+    start: usize,
+    value_start: usize,
+) -> Result<Filter, QueryError> {
+    let error = |position, message: String| QueryError { position, message };
+    if !FIELDS.contains(&field) {
+        return Err(error(
+            start,
+            format!(
+                "Unknown field {field}, expected one of {}",
+                FIELDS.join(", ")
+            ),
+        ));
+    }
+    if value.is_empty() {
+        return Err(error(
+            value_start,
+            format!("Expected a value after {field}:"),
+        ));
+    }
+    let flag = |value: &str| match value.to_lowercase().as_str() {
+        "yes" | "true" | "1" => Ok(true),
+        "no" | "false" | "0" => Ok(false),
+        _ => Err(error(
+            value_start,
+            format!("Expected yes or no after {field}:"),
+        )),
+    };
+    let filter = match field {
+        "name" => Filter::Name(value),
+        "description" => Filter::Description(value),
+        "repo" => Filter::Repository(value),
+        "provider" => Filter::Provider(value),
+        "installed" => Filter::Installed(flag(&value)?),
+        "updates" => Filter::Updates(flag(&value)?),
+        _ => {
+            let (ordering, or_equal, version) = [
+                (">=", Some(Ordering::Greater), true),
+                ("<=", Some(Ordering::Less), true),
+                ("=", None, true),
+                (">", Some(Ordering::Greater), false),
+                ("<", Some(Ordering::Less), false),
+            ]
+            .into_iter()
+            .find_map(|(operator, ordering, or_equal)| {
+                value
+                    .strip_prefix(operator)
+                    .map(|version| (ordering, or_equal, version))
+            })
+            .unwrap_or((None, false, &value));
+            if version.is_empty() {
+                return Err(error(
+                    value_start,
+                    "Expected a version to compare to".to_string(),
+                ));
+            }
+            Filter::Version(ordering, or_equal, version.to_string())
+        }
+    };
+    Ok(filter)
+}
@@ -0,0 +1,279 @@
+//! Finds packages by name or description without comparing every one of them, best matches first.
+
+use std::{cmp::Ordering, collections::HashMap};
+
+use rust_fuzzy_search::fuzzy_compare;
+
+use crate::{package::PackageData, query::Query};
+
+type Trigram = (char, char, char);
+
+/// How a package matched a search term, from the best to the worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchKind {
+    Exact,
+    Prefix,
+    Substring,
+    /// Only the description contains the term.
+    Description,
+    Fuzzy,
+}
+
+/// A package found by [`SearchIndex::search`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    /// Position of the package in [`SearchIndex::packages`].
+    pub package: usize,
+    /// The worst match among the terms.
+    pub kind: MatchKind,
+    /// Mean fuzzy score of the name against the terms.
+    pub similarity: f32,
+    length: usize,
+}
+
+impl Hit {
+    /// Orders the better matches first, the shorter names first among equals.
+    pub fn rank(&self, other: &Hit) -> Ordering {
+        self.kind
+            .cmp(&other.kind)
+            .then_with(|| other.similarity.total_cmp(&self.similarity))
+            .then_with(|| self.length.cmp(&other.length))
+    }
+}
+
+/// The packages of a provider with the trigrams of their names and descriptions, built once each
+/// time the provider loads so searching only looks at the packages sharing trigrams with the terms.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    packages: Vec<PackageData>,
+    /// Lowercase name, qualified name and description of each package.
+    keys: Vec<(String, String, String)>,
+    /// Packages whose name contains each trigram, in ascending order.
+    trigrams: HashMap<Trigram, Vec<u32>>,
+    /// Packages whose description contains each trigram, unpadded as descriptions are only
+    /// searched for the terms they contain.
+    description_trigrams: HashMap<Trigram, Vec<u32>>,
+}
+
+impl SearchIndex {
+    pub fn new(packages: Vec<PackageData>) -> Self {
+        let mut trigrams: HashMap<Trigram, Vec<u32>> = HashMap::new();
+        let mut description_trigrams: HashMap<Trigram, Vec<u32>> = HashMap::new();
+        let keys: Vec<(String, String, String)> = packages
+            .iter()
+            .map(|package| {
+                (
+                    package.name.to_lowercase(),
+                    package.qualified_name.to_lowercase(),
+                    package
+                        .description
+                        .as_deref()
+                        .unwrap_or_default()
+                        .to_lowercase(),
+                )
+            })
+            .collect();
+        for (position, (name, qualified_name, description)) in keys.iter().enumerate() {
+            let mut found = padded_trigrams(name);
+            found.extend(padded_trigrams(qualified_name));
+            add_postings(&mut trigrams, found, position);
+            let chars: Vec<char> = description.chars().collect();
+            let found = chars
+                .windows(3)
+                .map(|window| (window[0], window[1], window[2]))
+                .collect();
+            add_postings(&mut description_trigrams, found, position);
+        }
+        SearchIndex {
+            packages,
+            keys,
+            trigrams,
+            description_trigrams,
+        }
+    }
+
+    pub fn packages(&self) -> &[PackageData] {
+        &self.packages
+    }
+
+    /// The packages whose name, qualified name or description matches every term, in the order
+    /// of the index. `fuzzy` is the least fuzzy score accepted for names, `None` only accepts
+    /// names containing the term. Descriptions always have to contain it.
+    pub fn search(&self, terms: &[&str], fuzzy: Option<f32>) -> Vec<Hit> {
+        let terms: Vec<String> = terms.iter().map(|term| term.to_lowercase()).collect();
+        let mut candidates: Option<Vec<u32>> = None;
+        for term in &terms {
+            if let Some(found) = self.candidates(term, fuzzy) {
+                candidates = Some(match candidates {
+                    Some(candidates) => intersect(&candidates, &found),
+                    None => found,
+                });
+            }
+        }
+        let candidates: Box<dyn Iterator<Item = usize>> = match candidates {
+            Some(candidates) => Box::new(candidates.into_iter().map(|position| position as usize)),
+            None => Box::new(0..self.packages.len()),
+        };
+
+        candidates
+            .filter_map(|position| {
+                let (name, qualified_name, description) = &self.keys[position];
+                let mut kind = MatchKind::Exact;
+                let mut similarity = 0.0;
+                for term in &terms {
+                    kind = kind.max(match_kind(name, qualified_name, description, term, fuzzy)?);
+                    // Either way round, so neither a short nor a long name gets ahead
+                    similarity += fuzzy_compare(name, term).min(fuzzy_compare(term, name));
+                }
+                Some(Hit {
+                    package: position,
+                    kind,
+                    similarity: similarity / terms.len().max(1) as f32,
+                    length: name.len(),
+                })
+            })
+            .collect()
+    }
+
+    /// The packages that may match `term`, `None` when any of them may.
+    fn candidates(&self, term: &str, fuzzy: Option<f32>) -> Option<Vec<u32>> {
+        let names = self.name_candidates(term, fuzzy)?;
+        let descriptions = containing(&self.description_trigrams, term)?;
+        let mut found = names;
+        found.extend(descriptions);
+        found.sort_unstable();
+        found.dedup();
+        Some(found)
+    }
+
+    fn name_candidates(&self, term: &str, fuzzy: Option<f32>) -> Option<Vec<u32>> {
+        match fuzzy {
+            // Sharing no trigram with the term scores 0
+            Some(threshold) if threshold > 0.0 => {
+                let mut found: Vec<u32> = padded_trigrams(term)
+                    .iter()
+                    .filter_map(|trigram| self.trigrams.get(trigram))
+                    .flatten()
+                    .copied()
+                    .collect();
+                found.sort_unstable();
+                found.dedup();
+                Some(found)
+            }
+            Some(_) => None,
+            None => containing(&self.trigrams, term),
+        }
+    }
+}
+
+/// Whether `value` matches the search `term`, the way [`SearchIndex::search`] matches names.
+pub fn text_matches(value: &str, term: &str, fuzzy: Option<f32>) -> bool {
+    let (value, term) = (value.to_lowercase(), term.to_lowercase());
+    value.contains(&term)
+        || fuzzy.is_some_and(|threshold| fuzzy_compare(&value, &term) >= threshold)
+}
+
+/// The packages of the indexes matching `query`, best first, as positions of the index and of
+/// the package in it.
+pub fn find<'a>(
+    indexes: impl IntoIterator<Item = (&'a str, &'a SearchIndex)>,
+    query: &Query,
+    fuzzy: Option<f32>,
+) -> Vec<(usize, usize)> {
+    let terms = query.texts();
+    let mut hits: Vec<(usize, Hit)> = indexes
+        .into_iter()
+        .enumerate()
+        .flat_map(|(position, (provider, index))| {
+            index
+                .search(&terms, fuzzy)
+                .into_iter()
+                .filter(move |hit| {
+                    query.matches(&index.packages[hit.package], provider, |value, term| {
+                        text_matches(value, term, fuzzy)
+                    })
+                })
+                .map(move |hit| (position, hit))
+        })
+        .collect();
+    if !terms.is_empty() {
+        hits.sort_by(|(_, a), (_, b)| a.rank(b));
+    }
+    hits.into_iter()
+        .map(|(position, hit)| (position, hit.package))
+        .collect()
+}
+
+fn match_kind(
+    name: &str,
+    qualified_name: &str,
+    description: &str,
+    term: &str,
+    fuzzy: Option<f32>,
+) -> Option<MatchKind> {
+    if name == term || qualified_name == term {
+        Some(MatchKind::Exact)
+    } else if name.starts_with(term) || qualified_name.starts_with(term) {
+        Some(MatchKind::Prefix)
+    } else if qualified_name.contains(term) || name.contains(term) {
+        Some(MatchKind::Substring)
+    } else if description.contains(term) {
+        Some(MatchKind::Description)
+    } else {
+        let threshold = fuzzy?;
+        let similarity = fuzzy_compare(name, term).max(fuzzy_compare(qualified_name, term));
+        (similarity >= threshold).then_some(MatchKind::Fuzzy)
+    }
+}
+
+/// The trigrams `fuzzy_compare` scores with, padded with two spaces before and one after.
+fn padded_trigrams(value: &str) -> Vec<Trigram> {
+    let chars: Vec<char> = [' ', ' ']
+        .into_iter()
+        .chain(value.chars())
+        .chain([' '])
+        .collect();
+    chars
+        .windows(3)
+        .map(|window| (window[0], window[1], window[2]))
+        .collect()
+}
+
+/// The packages of `trigrams` that may contain `term`, as containing it means containing each of
+/// its trigrams. `None` when the term is too short to have any.
+fn containing(trigrams: &HashMap<Trigram, Vec<u32>>, term: &str) -> Option<Vec<u32>> {
+    let chars: Vec<char> = term.chars().collect();
+    let mut postings: Vec<&Vec<u32>> = Vec::new();
+    for window in chars.windows(3) {
+        match trigrams.get(&(window[0], window[1], window[2])) {
+            Some(posting) => postings.push(posting),
+            None => return Some(Vec::new()),
+        }
+    }
+    postings.sort_by_key(|posting| posting.len());
+    let (first, rest) = postings.split_first()?;
+    Some(
+        rest.iter()
+            .fold(first.to_vec(), |found, posting| intersect(&found, posting)),
+    )
+}
+
+fn add_postings(
+    trigrams: &mut HashMap<Trigram, Vec<u32>>,
+    mut found: Vec<Trigram>,
+    position: usize,
+) {
+    found.sort_unstable();
+    found.dedup();
+    for trigram in found {
+        trigrams.entry(trigram).or_default().push(position as u32);
+    }
+}
+
+/// Both ascending lists.
+fn intersect(a: &[u32], b: &[u32]) -> Vec<u32> {
+    a.iter()
+        .filter(|position| b.binary_search(position).is_ok())
+        .copied()
+        .collect()
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{fs, io::BufReader, path::PathBuf, sync::Mutex};
+
+use crate::{privilege::Privilege, utils};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub fuzzy_search: bool,
+    /// Least score, from 0 to 1, of a fuzzy match.
+    pub fuzzy_threshold: f32,
+    /// Overrides the detected way of running commands as root.
+    pub privilege: Option<Privilege>,
+    /// Minutes the sudo password is kept after it was entered, 0 keeps it until it is forgotten.
+    pub password_timeout: u32,
+    /// Optional columns shown in the package list, e.g. `description` or `size`.
+    pub columns: Vec<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            fuzzy_search: false,
+            fuzzy_threshold: 0.3,
+            privilege: None,
+            password_timeout: 15,
+            columns: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn set_bool(&mut self, prop: &str, value: bool) -> Result<()> {
+        if prop == "fuzzy_search" {
+            self.fuzzy_search = value
+        }
+        self.update_json()?;
+        Ok(())
+    }
+
+    pub fn update_json(&self) -> Result<()> {
+        let path = settings_path()?;
+        let file = fs::File::create(path)?;
+        serde_json::to_writer(file, self)?;
+        *CACHE.lock().unwrap() = Some(self.clone());
+        Ok(())
+    }
+
+    /// The fuzzy threshold when fuzzy search is on.
+    pub fn fuzzy(&self) -> Option<f32> {
+        self.fuzzy_search.then_some(self.fuzzy_threshold)
+    }
+}
+
+/// The settings as last read or written, so they are only read from disk once.
+static CACHE: Mutex<Option<Settings>> = Mutex::new(None);
+
+pub fn settings_path() -> Result<PathBuf> {
+    let mut path = utils::system_path()?;
+    path.push("setting.json");
+    Ok(path)
+}
+
+pub fn get() -> Result<Settings> {
+    if let Some(settings) = CACHE.lock().unwrap().clone() {
+        return Ok(settings);
+    }
+    let path = settings_path()?;
+    if !fs::exists(&path).unwrap_or(true) {
+        Settings::default().update_json()?;
+    }
+    let file = utils::open_file(path)?;
+    let reader = BufReader::new(file);
+    let settings: Settings = serde_json::from_reader(reader).expect("Failed to read settings file");
+    *CACHE.lock().unwrap() = Some(settings.clone());
+    Ok(settings)
+}
@@ -0,0 +1,166 @@
+use std::{cmp::Ordering, fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use secstr::SecVec;
+
+use crate::APP_ID;
+
+pub fn system_path() -> Result<PathBuf> {
+    let mut path = dirs::data_local_dir().context("User data directory not found")?;
+    path.push(APP_ID);
+    std::fs::create_dir_all(&path)?;
+    Ok(path)
+}
+
+pub fn open_file(path: PathBuf) -> Result<fs::File> {
+    let file = fs::File::open(path)?;
+    Ok(file)
+}
+
+pub fn pass_2_stdin(password: Option<SecVec<u8>>) -> Result<Vec<String>> {
+    let password = String::from_utf8(password.context("Missing password")?.unsecure().to_vec())?;
+    Ok([password].to_vec())
+}
+
+/// Parses `name old -> new` lines, as printed by `pacman -Qu` and `paru -Qua`.
+pub fn parse_upgrade_list(output: &str) -> Vec<(String, String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let name = columns.next()?;
+            let old = columns.next()?;
+            if columns.next()? != "->" {
+                return None;
+            }
+            let new = columns.next()?;
+            Some((name.to_string(), old.to_string(), new.to_string()))
+        })
+        .collect()
+}
+
+/// Parses sizes such as `1.2 MB` or `512 bytes`, as printed by GLib's `g_format_size`.
+pub fn parse_size(text: &str) -> Option<u64> {
+    let mut parts = text.split_whitespace();
+    let value: f64 = parts.next()?.replace(',', ".").parse().ok()?;
+    let factor = match parts.next().unwrap_or("bytes").to_lowercase().as_str() {
+        "b" | "byte" | "bytes" => 1.0,
+        "kb" => 1e3,
+        "mb" => 1e6,
+        "gb" => 1e9,
+        "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((value * factor).round() as u64)
+}
+
+/// Compares two package versions the way libalpm's `vercmp` does
+/// (`[epoch:]version[-release]`), which also fits rpm and most upstream versions.
+pub fn version_cmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let (epoch_a, version_a, release_a) = split_evr(a);
+    let (epoch_b, version_b, release_b) = split_evr(b);
+
+    segment_cmp(epoch_a, epoch_b)
+        .then_with(|| segment_cmp(version_a, version_b))
+        .then_with(|| match (release_a, release_b) {
+            (Some(release_a), Some(release_b)) => segment_cmp(release_a, release_b),
+            _ => Ordering::Equal,
+        })
+}
+
+fn split_evr(value: &str) -> (&str, &str, Option<&str>) {
+    let (epoch, rest) = match value.split_once(':') {
+        Some((epoch, rest)) if epoch.chars().all(|c| c.is_ascii_digit()) => (epoch, rest),
+        _ => ("0", value),
+    };
+    match rest.rsplit_once('-') {
+        Some((version, release)) => (epoch, version, Some(release)),
+        None => (epoch, rest, None),
+    }
+}
+
+fn segment_cmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        let (start_i, start_j) = (i, j);
+        while i < a.len() && !a[i].is_ascii_alphanumeric() {
+            i += 1;
+        }
+        while j < b.len() && !b[j].is_ascii_alphanumeric() {
+            j += 1;
+        }
+        if i >= a.len() || j >= b.len() {
+            break;
+        }
+        if i - start_i != j - start_j {
+            return (i - start_i).cmp(&(j - start_j));
+        }
+
+        let numeric = a[i].is_ascii_digit();
+        let end_i = segment_end(a, i, numeric);
+        let end_j = segment_end(b, j, numeric);
+        if end_j == j {
+            return if numeric {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+
+        let (segment_a, segment_b) = (&a[i..end_i], &b[j..end_j]);
+        let ordering = if numeric {
+            let segment_a = trim_zeros(segment_a);
+            let segment_b = trim_zeros(segment_b);
+            segment_a
+                .len()
+                .cmp(&segment_b.len())
+                .then_with(|| segment_a.cmp(segment_b))
+        } else {
+            segment_a.cmp(segment_b)
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+        i = end_i;
+        j = end_j;
+    }
+
+    if i >= a.len() && j >= b.len() {
+        Ordering::Equal
+    } else if (i >= a.len() && !b[j].is_ascii_alphabetic())
+        || (i < a.len() && a[i].is_ascii_alphabetic())
+    {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+fn segment_end(value: &[u8], start: usize, numeric: bool) -> usize {
+    value[start..]
+        .iter()
+        .position(|c| {
+            if numeric {
+                !c.is_ascii_digit()
+            } else {
+                !c.is_ascii_alphabetic()
+            }
+        })
+        .map_or(value.len(), |position| start + position)
+}
+
+fn trim_zeros(value: &[u8]) -> &[u8] {
+    let start = value.iter().position(|c| *c != b'0').unwrap_or(value.len());
+    &value[start..]
+}
@@ -0,0 +1,95 @@
+use package_manager_core::{
+    catalog::Catalog,
+    package::{PackageData, PackageStatus},
+};
+
+fn package(name: &str, status: PackageStatus, installed: Option<&str>) -> PackageData {
+    PackageData {
+        status,
+        repository: "extra".to_string(),
+        name: name.to_string(),
+        installed_version: installed.map(str::to_string),
+        available_version: Some("2.0".to_string()),
+        qualified_name: format!("extra/{name}"),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn catalog_keeps_the_last_packages_of_each_provider() {
+    let mut catalog = Catalog::open_at(":memory:").unwrap();
+    catalog
+        .save(
+            "Pacman",
+            &[package("vim", PackageStatus::NotInstalled, None)],
+        )
+        .unwrap();
+    let refreshed = catalog
+        .save(
+            "Pacman",
+            &[
+                package("git", PackageStatus::UpdateAvailable, Some("1.0")),
+                package("zsh", PackageStatus::UpToDate, Some("2.0")),
+            ],
+        )
+        .unwrap();
+    catalog
+        .save("Flatpak", &[package("gimp", PackageStatus::Foreign, None)])
+        .unwrap();
+
+    let snapshot = catalog.load("Pacman").unwrap().unwrap();
+    assert_eq!(
+        snapshot.refreshed.elapsed().unwrap().as_secs(),
+        refreshed.elapsed().unwrap().as_secs()
+    );
+    let mut packages = snapshot.packages;
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(packages.len(), 2);
+    assert_eq!(packages[0].qualified_name, "extra/git");
+    assert_eq!(packages[0].status, PackageStatus::UpdateAvailable);
+    assert_eq!(packages[0].installed_version.as_deref(), Some("1.0"));
+    assert_eq!(packages[0].available_version.as_deref(), Some("2.0"));
+    assert_eq!(packages[1].status, PackageStatus::UpToDate);
+
+    assert!(catalog.load("Dnf").unwrap().is_none());
+}
+
+#[test]
+fn catalog_keeps_the_metadata_of_packages() {
+    let mut catalog = Catalog::open_at(":memory:").unwrap();
+    let vim = PackageData {
+        description: Some("Vi Improved, a highly configurable text editor".to_string()),
+        installed_size: Some(4_700_000),
+        download_size: Some(2_100_000),
+        licenses: vec!["custom:vim".to_string(), "GPL-2.0".to_string()],
+        url: Some("https://www.vim.org".to_string()),
+        install_date: Some(1_700_000_000),
+        popularity: Some(0.5),
+        ..package("vim", PackageStatus::UpToDate, Some("2.0"))
+    };
+    catalog
+        .save(
+            "Pacman",
+            &[vim, package("zsh", PackageStatus::NotInstalled, None)],
+        )
+        .unwrap();
+
+    let mut packages = catalog.load("Pacman").unwrap().unwrap().packages;
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    let vim = &packages[0];
+    assert_eq!(
+        vim.description.as_deref(),
+        Some("Vi Improved, a highly configurable text editor")
+    );
+    assert_eq!(vim.installed_size, Some(4_700_000));
+    assert_eq!(vim.download_size, Some(2_100_000));
+    assert_eq!(vim.licenses, ["custom:vim", "GPL-2.0"]);
+    assert_eq!(vim.url.as_deref(), Some("https://www.vim.org"));
+    assert_eq!(vim.packager, None);
+    assert_eq!(vim.install_date, Some(1_700_000_000));
+    assert_eq!(vim.popularity, Some(0.5));
+
+    let zsh = &packages[1];
+    assert_eq!(zsh.description, None);
+    assert!(zsh.licenses.is_empty());
+}
@@ -0,0 +1,19 @@
+CREATE TABLE ids(id TEXT NOT NULL);
+CREATE TABLE names(name TEXT NOT NULL);
+CREATE TABLE versions(version TEXT NOT NULL);
+CREATE TABLE manifest(
+    id INT64 NOT NULL,
+    name INT64 NOT NULL,
+    moniker INT64 NOT NULL,
+    version INT64 NOT NULL,
+    channel INT64 NOT NULL,
+    pathpart INT64 NOT NULL
+);
+
+INSERT INTO ids(rowid, id) VALUES (1, 'Git.Git'), (2, 'Mozilla.Firefox');
+INSERT INTO names(rowid, name) VALUES (1, 'Git'), (2, 'Mozilla Firefox');
+INSERT INTO versions(rowid, version) VALUES (1, '2.44.0'), (2, '2.45.0'), (3, '125.0.1');
+INSERT INTO manifest(id, name, moniker, version, channel, pathpart) VALUES
+    (1, 1, 0, 1, 0, 0),
+    (1, 1, 0, 2, 0, 0),
+    (2, 2, 0, 3, 0, 0);
@@ -0,0 +1,128 @@
+use package_manager_core::{
+    history::{Action, Entry, History, Status},
+    package::{PackageData, PackageStatus},
+    provider::{Change, Operation},
+};
+
+fn installed(qualified_name: &str, version: &str) -> PackageData {
+    PackageData {
+        status: PackageStatus::UpToDate,
+        name: qualified_name.to_string(),
+        qualified_name: qualified_name.to_string(),
+        installed_version: Some(version.to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn history_keeps_transactions_with_their_versions() {
+    let mut history = History::open_at(":memory:").unwrap();
+    let first = history
+        .begin("Pacman", &[Entry::new("extra/vim", Action::Install, None)])
+        .unwrap();
+    history
+        .finish(
+            first,
+            Status::Failed,
+            Some(1),
+            "error: target not found: vim\n",
+        )
+        .unwrap();
+    let second = history
+        .begin(
+            "Pacman",
+            &[
+                Entry::new("extra/git", Action::Update, Some("2.44.0-1".to_string())),
+                Entry::new("extra/zsh", Action::Remove, Some("5.9-5".to_string())),
+                Entry::new("extra/htop", Action::Install, None),
+            ],
+        )
+        .unwrap();
+
+    let transactions = history.transactions(10).unwrap();
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].id, second);
+    assert_eq!(transactions[0].status, Status::Running);
+    assert_eq!(transactions[0].finished, None);
+    assert_eq!(transactions[1].status, Status::Failed);
+    assert_eq!(transactions[1].exit_code, Some(1));
+    assert!(transactions[1].finished.is_some());
+    assert_eq!(
+        history.transcript(first).unwrap(),
+        "error: target not found: vim\n"
+    );
+
+    history
+        .finish(second, Status::Succeeded, Some(0), "")
+        .unwrap();
+    history
+        .record_versions_after(
+            second,
+            &[
+                installed("extra/git", "2.45.0-1"),
+                installed("extra/htop", "3.3.0-1"),
+            ],
+        )
+        .unwrap();
+    let transaction = &history.transactions(1).unwrap()[0];
+    assert_eq!(transaction.provider, "Pacman");
+    assert_eq!(transaction.status, Status::Succeeded);
+    assert_eq!(
+        transaction.entries,
+        [
+            Entry {
+                package: "extra/git".to_string(),
+                action: Action::Update,
+                version_before: Some("2.44.0-1".to_string()),
+                version_after: Some("2.45.0-1".to_string()),
+            },
+            Entry {
+                package: "extra/zsh".to_string(),
+                action: Action::Remove,
+                version_before: Some("5.9-5".to_string()),
+                version_after: None,
+            },
+            Entry {
+                package: "extra/htop".to_string(),
+                action: Action::Install,
+                version_before: None,
+                version_after: Some("3.3.0-1".to_string()),
+            },
+        ]
+    );
+
+    let undo = transaction.undo();
+    assert_eq!(
+        undo.changes,
+        [
+            Change {
+                operation: Operation::Install,
+                package: "extra/zsh".to_string(),
+            },
+            Change {
+                operation: Operation::Remove,
+                package: "extra/htop".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        undo.downgrades,
+        [("extra/git".to_string(), "2.44.0-1".to_string())]
+    );
+}
+
+#[test]
+fn nothing_to_undo_before_the_provider_reloads() {
+    let mut history = History::open_at(":memory:").unwrap();
+    history
+        .begin(
+            "Dnf",
+            &[Entry::new(
+                "bash.x86_64",
+                Action::Update,
+                Some("5.2.26-1.fc39".to_string()),
+            )],
+        )
+        .unwrap();
+    assert!(history.transactions(1).unwrap()[0].undo().is_empty());
+}
@@ -0,0 +1,681 @@
+use std::sync::Arc;
+
+use package_manager_core::{
+    command::{Cmd, CommandFailed, Line, Outcome},
+    details,
+    error::ProviderError,
+    executor::{Executor, Output, Recorder, Replay, System},
+    package::{PackageData, PackageStatus},
+    provider::{Change, Operation, ProviderActions},
+    providers_impl::{
+        dnf::Dnf, flatpak::Flatpak, pacman::Pacman, paru::Paru, protonge::ProtonGE, soar::Soar,
+        winget::Winget,
+    },
+};
+
+const DNF_LIST: &str = "LC_ALL=C dnf list --all -q";
+const FLATPAK_LIST: &str =
+    "LC_ALL=C flatpak list --columns=name,ref,branch,version,origin,arch,description,size -j";
+const FLATPAK_REMOTE_LS: &str = "LC_ALL=C flatpak remote-ls \
+    --columns=name,ref,branch,version,origin,arch,description,installed-size,download-size -j";
+const PROTONGE_RELEASES: &str =
+    "https://api.github.com/repos/GloriousEggroll/proton-ge-custom/releases";
+
+fn find<'a>(packages: &'a [PackageData], name: &str) -> &'a PackageData {
+    packages
+        .iter()
+        .find(|package| package.name == name)
+        .unwrap_or_else(|| panic!("{name} not found in {packages:#?}"))
+}
+
+fn exit_code(code: i32) -> Output {
+    Output {
+        code: Some(code),
+        ..Output::default()
+    }
+}
+
+#[test]
+fn dnf_parses_installed_and_available_packages() {
+    let replay = Replay::new().record(DNF_LIST, include_str!("fixtures/dnf/list.txt"));
+    let mut dnf = Dnf::with_executor(Arc::new(replay));
+    dnf.load_packages().unwrap();
+    let packages = dnf.packages();
+
+    let bash = find(&packages, "bash.x86_64");
+    assert_eq!(bash.status, PackageStatus::UpdateAvailable);
+    assert_eq!(bash.installed_version.as_deref(), Some("5.2.26-1.fc39"));
+    assert_eq!(bash.available_version.as_deref(), Some("5.2.26-3.fc39"));
+    assert_eq!(bash.repository, "updates");
+
+    let network_manager = find(&packages, "NetworkManager.x86_64");
+    assert_eq!(network_manager.status, PackageStatus::UpToDate);
+    assert_eq!(network_manager.repository, "updates");
+
+    assert_eq!(
+        find(&packages, "kernel-core.x86_64").status,
+        PackageStatus::UpToDate
+    );
+    assert_eq!(
+        find(&packages, "local-tool.noarch").status,
+        PackageStatus::Foreign
+    );
+    assert_eq!(
+        find(&packages, "0ad.x86_64").status,
+        PackageStatus::NotInstalled
+    );
+
+    // Long names push the rest of the row to the next line
+    let texlive = find(&packages, "texlive-collection-latexrecommended.noarch");
+    assert_eq!(texlive.status, PackageStatus::UpToDate);
+    assert_eq!(
+        texlive.installed_version.as_deref(),
+        Some("9:svn65512-62.fc39")
+    );
+    let intelex = find(&packages, "python3-scikit-learn-intelex-devel.x86_64");
+    assert_eq!(
+        intelex.available_version.as_deref(),
+        Some("2024.0.1-1.fc39")
+    );
+    assert!(packages.iter().all(|package| !package.name.is_empty()));
+
+    assert_eq!(packages.len(), 8);
+    assert_eq!(dnf.installed(), 6);
+}
+
+#[test]
+fn dnf_parses_dnf5_headers() {
+    let replay = Replay::new().record(DNF_LIST, include_str!("fixtures/dnf/list_dnf5.txt"));
+    let mut dnf = Dnf::with_executor(Arc::new(replay));
+    dnf.load_packages().unwrap();
+    let packages = dnf.packages();
+
+    assert_eq!(
+        find(&packages, "bash.x86_64").status,
+        PackageStatus::UpdateAvailable
+    );
+    assert_eq!(
+        find(&packages, "glibc.x86_64").status,
+        PackageStatus::UpToDate
+    );
+    assert_eq!(
+        find(&packages, "cowsay.noarch").status,
+        PackageStatus::NotInstalled
+    );
+    assert_eq!(packages.len(), 3);
+}
+
+#[test]
+fn dnf_skips_malformed_rows() {
+    let replay = Replay::new().record(DNF_LIST, include_str!("fixtures/dnf/list_malformed.txt"));
+    let mut dnf = Dnf::with_executor(Arc::new(replay));
+    dnf.load_packages().unwrap();
+    let packages = dnf.packages();
+
+    assert_eq!(packages.len(), 1);
+    assert_eq!(packages[0].name, "vim-enhanced.x86_64");
+    assert_eq!(
+        packages[0].available_version.as_deref(),
+        Some("2:9.1.113-1.fc39")
+    );
+}
+
+#[test]
+fn dnf_asks_for_untranslated_output() {
+    // Without LC_ALL=C the section headers would be translated
+    let replay = Arc::new(Replay::new());
+    let mut dnf = Dnf::with_executor(replay.clone());
+    assert!(dnf.load_packages().is_err());
+    assert_eq!(replay.commands(), [DNF_LIST]);
+}
+
+#[test]
+fn dnf_lists_upgrades_until_obsoleted_packages() {
+    let replay = Replay::new()
+        .record(DNF_LIST, include_str!("fixtures/dnf/list.txt"))
+        .record_output(
+            "LC_ALL=C dnf check-update -q",
+            Output {
+                code: Some(100),
+                stdout: include_str!("fixtures/dnf/check_update.txt").to_string(),
+                stderr: String::new(),
+            },
+        );
+    let mut dnf = Dnf::with_executor(Arc::new(replay));
+    dnf.load_packages().unwrap();
+    let upgrades = dnf.upgradable().unwrap();
+
+    let names: Vec<&str> = upgrades
+        .iter()
+        .map(|package| package.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "bash.x86_64",
+            "firefox.x86_64",
+            "python3-scikit-learn-intelex-devel.x86_64"
+        ]
+    );
+    let bash = find(&upgrades, "bash.x86_64");
+    assert_eq!(bash.installed_version.as_deref(), Some("5.2.26-1.fc39"));
+    assert_eq!(bash.available_version.as_deref(), Some("5.2.26-3.fc39"));
+}
+
+#[test]
+fn dnf_check_update_failure_is_an_error() {
+    let replay = Replay::new().record_output("LC_ALL=C dnf check-update -q", exit_code(1));
+    let dnf = Dnf::with_executor(Arc::new(replay));
+    let err = dnf.upgradable().unwrap_err();
+    assert!(matches!(
+        err,
+        ProviderError::CommandFailed { code: Some(1), .. }
+    ));
+}
+
+#[test]
+fn dnf_unknown_package_is_not_found() {
+    let replay = Replay::new().record_output(
+        "dnf info nope",
+        Output {
+            code: Some(1),
+            stdout: String::new(),
+            stderr: "Error: No matching Packages to list\n".to_string(),
+        },
+    );
+    let dnf = Dnf::with_executor(Arc::new(replay));
+    let err = dnf.package_info("nope".to_string()).unwrap_err();
+    assert!(matches!(err, ProviderError::PackageNotFound(_)), "{err:?}");
+}
+
+#[test]
+fn dnf_package_details_keep_the_first_repository() {
+    let replay = Replay::new().record(
+        "dnf info vim-enhanced",
+        include_str!("fixtures/dnf/info.txt"),
+    );
+    let dnf = Dnf::with_executor(Arc::new(replay));
+    let details = dnf.package_details("vim-enhanced".to_string()).unwrap();
+    assert!(details
+        .fields
+        .contains(&("Version".to_string(), "9.1.083".to_string())));
+    assert!(details.fields.contains(&(
+        "Description".to_string(),
+        "VIM (VIsual editor iMproved) is an updated and improved version of\nthe vi editor."
+            .to_string()
+    )));
+    assert_eq!(
+        details.links,
+        [("URL".to_string(), "http://www.vim.org/".to_string())]
+    );
+    assert_eq!(
+        details
+            .fields
+            .iter()
+            .filter(|(label, _)| label == "Name")
+            .count(),
+        1
+    );
+}
+
+fn flatpak() -> Flatpak {
+    let replay = Replay::new()
+        .record(FLATPAK_LIST, include_str!("fixtures/flatpak/list.json"))
+        .record(
+            FLATPAK_REMOTE_LS,
+            include_str!("fixtures/flatpak/remote_ls.json"),
+        )
+        .record(
+            &format!("{FLATPAK_REMOTE_LS} --updates"),
+            include_str!("fixtures/flatpak/remote_ls_updates.json"),
+        );
+    let mut flatpak = Flatpak::with_executor(Arc::new(replay));
+    flatpak.load_packages().unwrap();
+    flatpak
+}
+
+#[test]
+fn flatpak_parses_json_lists() {
+    let flatpak = flatpak();
+    let packages = flatpak.packages();
+
+    let firefox = find(&packages, "Firefox");
+    assert_eq!(firefox.status, PackageStatus::UpdateAvailable);
+    assert_eq!(
+        firefox.qualified_name,
+        "flathub org.mozilla.firefox/x86_64/stable"
+    );
+    assert_eq!(firefox.repository, "flathub stable x86_64");
+    assert_eq!(
+        firefox.description.as_deref(),
+        Some("Fast, Private & Safe Web Browser")
+    );
+    // The size on disk of the installed version, not of the update
+    assert_eq!(firefox.installed_size, Some(263_000_000));
+    assert_eq!(firefox.download_size, Some(108_600_000));
+
+    assert_eq!(
+        find(&packages, "Freedesktop Platform").status,
+        PackageStatus::UpToDate
+    );
+    let snapshot = find(&packages, "Bildschirmfoto");
+    assert_eq!(snapshot.status, PackageStatus::NotInstalled);
+    assert_eq!(snapshot.installed_size, Some(5_200_000));
+
+    let sideloaded = find(&packages, "Sideloaded");
+    assert_eq!(sideloaded.status, PackageStatus::Foreign);
+    assert_eq!(sideloaded.available_version, None);
+
+    assert_eq!(packages.len(), 5);
+    assert_eq!(flatpak.installed(), 4);
+}
+
+#[test]
+fn flatpak_keeps_translated_names_and_empty_versions() {
+    let packages = flatpak().packages();
+    let epiphany = find(&packages, "ウェブ");
+    // An empty installed version can't be compared
+    assert_eq!(epiphany.status, PackageStatus::UpToDate);
+    assert_eq!(epiphany.installed_version.as_deref(), Some(""));
+}
+
+#[test]
+fn flatpak_lists_updates_with_installed_versions() {
+    let upgrades = flatpak().upgradable().unwrap();
+    assert_eq!(upgrades.len(), 1);
+    assert_eq!(upgrades[0].installed_version.as_deref(), Some("123.0"));
+    assert_eq!(upgrades[0].available_version.as_deref(), Some("124.0"));
+}
+
+#[test]
+fn flatpak_package_details_need_the_runtime() {
+    let replay = Replay::new().record(
+        "flatpak remote-info flathub org.mozilla.firefox/x86_64/stable",
+        include_str!("fixtures/flatpak/remote_info.txt"),
+    );
+    let flatpak = Flatpak::with_executor(Arc::new(replay));
+    let details = flatpak
+        .package_details("flathub org.mozilla.firefox/x86_64/stable".to_string())
+        .unwrap();
+    assert_eq!(
+        details.dependencies,
+        ["org.freedesktop.Platform/x86_64/23.08"]
+    );
+    assert_eq!(
+        details.fields[0],
+        ("ID".to_string(), "org.mozilla.firefox".to_string())
+    );
+    assert!(details.fields.contains(&(
+        "Subject".to_string(),
+        "Export org.mozilla.firefox".to_string()
+    )));
+}
+
+#[test]
+fn flatpak_rejects_output_that_is_not_json() {
+    // flatpak before 1.15 ignores -j and prints a table
+    let replay = Replay::new().record(
+        FLATPAK_LIST,
+        include_str!("fixtures/flatpak/list_table.txt"),
+    );
+    let mut flatpak = Flatpak::with_executor(Arc::new(replay));
+    match flatpak.load_packages().unwrap_err() {
+        ProviderError::Parse { line, .. } => assert!(line.starts_with("Firefox")),
+        err => panic!("{err:?}"),
+    }
+}
+
+#[test]
+fn flatpak_groups_installs_by_remote() {
+    let command = "sh -c 'flatpak install flathub org.mozilla.firefox/x86_64/stable \
+                   org.gnome.Epiphany/x86_64/stable -y --noninteractive && \
+                   flatpak remove com.example.Sideloaded/x86_64/master -y --noninteractive'";
+    let replay = Replay::new().record(command, "Installing 2/2…\n");
+    let flatpak = Flatpak::with_executor(Arc::new(replay));
+    let changes = vec![
+        Change {
+            operation: Operation::Install,
+            package: "flathub org.mozilla.firefox/x86_64/stable".to_string(),
+        },
+        Change {
+            operation: Operation::Remove,
+            package: "sideloaded-origin com.example.Sideloaded/x86_64/master".to_string(),
+        },
+        Change {
+            operation: Operation::Install,
+            package: "flathub org.gnome.Epiphany/x86_64/stable".to_string(),
+        },
+    ];
+    let mut stream = flatpak.execute(None, changes).unwrap();
+    let lines: Vec<Line> = stream.by_ref().collect();
+    assert_eq!(lines, [Line::Stdout("Installing 2/2…".to_string())]);
+    assert_eq!(stream.close().unwrap(), Outcome::Success);
+}
+
+#[test]
+fn soar_strips_colours_and_skips_decorations() {
+    let replay = Replay::new().record("soar list", include_str!("fixtures/soar/list.txt"));
+    let mut soar = Soar::with_executor(Arc::new(replay));
+    soar.load_packages().unwrap();
+    let packages = soar.packages();
+
+    let bat = find(&packages, "bat");
+    assert_eq!(bat.status, PackageStatus::UpToDate);
+    assert_eq!(bat.qualified_name, "bat#github.com.sharkdp.bat:bincache");
+    assert_eq!(bat.installed_version.as_deref(), Some("0.24.0"));
+    assert_eq!(bat.repository, "bincache");
+
+    assert_eq!(find(&packages, "btop").status, PackageStatus::NotInstalled);
+    assert_eq!(
+        find(&packages, "fastfetch").status,
+        PackageStatus::NotInstalled
+    );
+    assert_eq!(packages.len(), 3);
+    assert_eq!(soar.installed(), 1);
+}
+
+#[test]
+fn soar_lists_updates_from_the_installed_versions() {
+    let replay = Replay::new()
+        .record("soar list", include_str!("fixtures/soar/list.txt"))
+        .record("soar info", include_str!("fixtures/soar/info.txt"));
+    let mut soar = Soar::with_executor(Arc::new(replay));
+    soar.load_packages().unwrap();
+    let upgrades = soar.upgradable().unwrap();
+
+    assert_eq!(upgrades.len(), 1);
+    let bat = find(&upgrades, "bat");
+    assert_eq!(bat.status, PackageStatus::UpdateAvailable);
+    assert_eq!(bat.qualified_name, "bat#github.com.sharkdp.bat:bincache");
+    assert_eq!(bat.installed_version.as_deref(), Some("0.23.0"));
+    assert_eq!(bat.available_version.as_deref(), Some("0.24.0"));
+}
+
+#[test]
+fn pacman_lists_upgrades_from_the_synced_databases() {
+    let replay = Replay::new().record("pacman -Qu", include_str!("fixtures/pacman/qu.txt"));
+    let pacman = Pacman::with_executor(Arc::new(replay));
+    let upgrades = pacman.upgradable().unwrap();
+
+    // vim is in IgnorePkg
+    assert_eq!(upgrades.len(), 2);
+    let mesa = find(&upgrades, "mesa");
+    assert_eq!(mesa.installed_version.as_deref(), Some("1:24.0.1-1"));
+    assert_eq!(mesa.available_version.as_deref(), Some("1:24.0.2-1"));
+}
+
+#[test]
+fn pacman_up_to_date_exit_code_is_not_an_error() {
+    let replay = Replay::new().record_output("pacman -Qu", exit_code(1));
+    let pacman = Pacman::with_executor(Arc::new(replay));
+    assert!(pacman.upgradable().unwrap().is_empty());
+}
+
+#[test]
+fn pacman_failing_to_list_upgrades_falls_back_to_the_loaded_packages() {
+    let pacman = Pacman::with_executor(Arc::new(Replay::new()));
+    assert!(pacman.upgradable().unwrap().is_empty());
+}
+
+#[test]
+fn pacman_package_info() {
+    let info = include_str!("fixtures/pacman/info.txt");
+    let replay = Replay::new().record("pacman -Si bash", info);
+    let pacman = Pacman::with_executor(Arc::new(replay));
+    assert_eq!(pacman.package_info("bash".to_string()).unwrap(), info);
+    assert!(pacman.package_info("-bash".to_string()).is_err());
+}
+
+#[test]
+fn pacman_package_details() {
+    let replay = Replay::new().record("pacman -Si bash", include_str!("fixtures/pacman/info.txt"));
+    let pacman = Pacman::with_executor(Arc::new(replay));
+    let details = pacman.package_details("bash".to_string()).unwrap();
+    assert_eq!(
+        details.dependencies,
+        ["readline", "libreadline.so=8-64", "glibc", "ncurses"]
+    );
+    assert_eq!(
+        details::dependency_name(&details.dependencies[1]),
+        "libreadline.so"
+    );
+    assert_eq!(
+        details.optional_dependencies,
+        [
+            (
+                "bash-completion".to_string(),
+                "for tab completion".to_string()
+            ),
+            ("bash-docs".to_string(), "for the documentation".to_string()),
+        ]
+    );
+    assert_eq!(
+        details.links,
+        [(
+            "URL".to_string(),
+            "https://www.gnu.org/software/bash/bash.html".to_string()
+        )]
+    );
+    assert_eq!(
+        details.fields[0],
+        ("Repository".to_string(), "core".to_string())
+    );
+    assert!(details
+        .fields
+        .contains(&("Groups".to_string(), "None".to_string())));
+    assert_eq!(details.release_notes, None);
+}
+
+#[test]
+fn pacman_locked_database() {
+    let replay = Replay::new().record_output(
+        "pacman -Si bash",
+        Output {
+            code: Some(1),
+            stdout: String::new(),
+            stderr: "error: failed to init transaction (unable to lock database)\n\
+                     error: could not lock database: File exists\n"
+                .to_string(),
+        },
+    );
+    let pacman = Pacman::with_executor(Arc::new(replay));
+    let err = pacman.package_info("bash".to_string()).unwrap_err();
+    assert!(matches!(err, ProviderError::LockedDatabase(_)), "{err:?}");
+}
+
+#[test]
+fn failures_are_told_apart_by_their_output() {
+    let failure = |code, stderr: &str| {
+        Output {
+            code: Some(code),
+            stdout: String::new(),
+            stderr: stderr.to_string(),
+        }
+        .success(&[0])
+        .unwrap_err()
+    };
+    assert!(matches!(
+        failure(1, "sudo: 3 incorrect password attempts"),
+        ProviderError::AuthFailed
+    ));
+    assert!(matches!(
+        failure(
+            126,
+            "Error executing command as another user: Request dismissed"
+        ),
+        ProviderError::Cancelled
+    ));
+    assert!(matches!(
+        failure(
+            1,
+            "error: Unable to load summary from remote flathub: Could not resolve hostname"
+        ),
+        ProviderError::Network(_)
+    ));
+    assert!(matches!(
+        failure(1, "error: target not found: nope"),
+        ProviderError::PackageNotFound(_)
+    ));
+    assert!(matches!(
+        failure(127, "sh: 1: paru: not found"),
+        ProviderError::Unavailable(program) if program == "paru"
+    ));
+    assert!(matches!(
+        failure(2, "something else"),
+        ProviderError::CommandFailed { code: Some(2), .. }
+    ));
+}
+
+#[test]
+fn missing_program_is_unavailable() {
+    let err = System
+        .output(&Cmd::new("package-manager-missing-tool"))
+        .unwrap_err();
+    assert!(
+        matches!(&err, ProviderError::Unavailable(program) if program == "package-manager-missing-tool"),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn paru_parses_aur_upgrades() {
+    let replay = Replay::new().record("paru -Qua", include_str!("fixtures/paru/qua.txt"));
+    let paru = Paru::with_executor(Arc::new(replay));
+    let upgrades = paru.upgradable().unwrap();
+
+    assert_eq!(upgrades.len(), 2);
+    let code = find(&upgrades, "visual-studio-code-bin");
+    assert_eq!(code.repository, "AUR");
+    assert_eq!(code.available_version.as_deref(), Some("1.87.2-1"));
+}
+
+#[test]
+fn paru_nothing_to_upgrade() {
+    let replay = Replay::new().record_output("paru -Qua", exit_code(1));
+    let paru = Paru::with_executor(Arc::new(replay));
+    assert!(paru.upgradable().unwrap().is_empty());
+}
+
+#[test]
+fn winget_runs_one_command_per_package() {
+    let command = "sh -c 'winget upgrade -h -e --id Mozilla.Firefox && \
+                   winget upgrade -h -e --id Git.Git'";
+    let replay = Replay::new().record_output(
+        command,
+        Output {
+            code: Some(1),
+            stdout: "Successfully installed\n".to_string(),
+            stderr: "No applicable upgrade found.\n".to_string(),
+        },
+    );
+    let winget = Winget::with_executor(Arc::new(replay));
+    let mut stream = winget
+        .update_selected(None, vec!["Mozilla.Firefox".into(), "Git.Git".into()])
+        .unwrap();
+    let lines: Vec<Line> = stream.by_ref().collect();
+    assert_eq!(
+        lines,
+        [
+            Line::Stdout("Successfully installed".to_string()),
+            Line::Stderr("No applicable upgrade found.".to_string()),
+        ]
+    );
+    let err = stream.close().unwrap_err();
+    let failed = err.downcast_ref::<CommandFailed>().unwrap();
+    assert_eq!(failed.code, Some(1));
+    assert_eq!(failed.stderr, "No applicable upgrade found.");
+}
+
+#[test]
+fn winget_package_info() {
+    let info = include_str!("fixtures/winget/show.txt");
+    let replay = Replay::new().record("winget show Mozilla.Firefox", info);
+    let winget = Winget::with_executor(Arc::new(replay));
+    assert_eq!(
+        winget.package_info("Mozilla.Firefox".to_string()).unwrap(),
+        info
+    );
+}
+
+#[test]
+fn protonge_lists_releases_and_downloads_their_tarball() {
+    let replay = Arc::new(
+        Replay::new()
+            .record_download(
+                PROTONGE_RELEASES,
+                include_str!("fixtures/protonge/releases.json"),
+            )
+            .record_download(
+                "https://api.github.com/repos/GloriousEggroll/proton-ge-custom/releases/170563212/assets",
+                include_str!("fixtures/protonge/assets.json"),
+            ),
+    );
+    let mut protonge = ProtonGE::with_executor(replay.clone());
+    protonge.load_packages().unwrap();
+    let packages = protonge.packages();
+
+    let latest = find(&packages, "GE-Proton9-20");
+    assert_eq!(latest.available_version.as_deref(), Some("9-20"));
+    assert_eq!(
+        latest.url.as_deref(),
+        Some("https://github.com/GloriousEggroll/proton-ge-custom/releases/tag/GE-Proton9-20")
+    );
+    assert_eq!(
+        protonge
+            .package_details("GE-Proton9-20".to_string())
+            .unwrap()
+            .release_notes
+            .as_deref(),
+        Some("- wine: updated to latest bleeding edge\r\n- dxvk: updated to latest git")
+    );
+    find(&packages, "GE-Proton9-19");
+
+    // The download itself isn't recorded, only the command asked for is checked
+    let _ = protonge.install(None, "GE-Proton9-20".to_string());
+    let download = replay.commands()[0].clone();
+    assert!(
+        download.contains(" https://github.com/GloriousEggroll/proton-ge-custom/releases/download/GE-Proton9-20/GE-Proton9-20.tar.gz "),
+        "{download}"
+    );
+}
+
+#[test]
+fn protonge_without_releases_has_nothing_to_update() {
+    let replay = Arc::new(Replay::new().record("echo 'Nothing to do.'", "Nothing to do.\n"));
+    let protonge = ProtonGE::with_executor(replay.clone());
+    let mut stream = protonge
+        .update_selected(None, vec!["GE-Proton9-1".to_string()])
+        .unwrap();
+    assert_eq!(
+        stream.next(),
+        Some(Line::Stdout("Nothing to do.".to_string()))
+    );
+    assert_eq!(stream.close().unwrap(), Outcome::Success);
+    assert_eq!(replay.commands(), ["echo 'Nothing to do.'"]);
+}
+
+#[test]
+fn recorder_captures_what_replay_answers() {
+    let replay = Replay::new().record("paru -Qua", include_str!("fixtures/paru/qua.txt"));
+    let recorder = Arc::new(Recorder::new(Arc::new(replay)));
+    let paru = Paru::with_executor(recorder.clone());
+    paru.upgradable().unwrap();
+
+    let records = recorder.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].command, "paru -Qua");
+    assert_eq!(records[0].output.code, Some(0));
+    assert_eq!(
+        records[0].output.stdout,
+        include_str!("fixtures/paru/qua.txt")
+    );
+}
+
+#[test]
+fn replay_fails_for_unrecorded_commands() {
+    let replay = Replay::new();
+    let err = replay
+        .run(&package_manager_core::command::Cmd::new("flatpak").arg("--version"))
+        .unwrap_err();
+    assert!(err.to_string().contains("flatpak --version"));
+}
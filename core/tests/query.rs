@@ -0,0 +1,110 @@
+use package_manager_core::{
+    package::{PackageData, PackageStatus},
+    query::Query,
+};
+
+fn package(qualified_name: &str, status: PackageStatus, version: &str) -> PackageData {
+    let (repository, name) = qualified_name.split_once('/').unwrap();
+    PackageData {
+        status,
+        repository: repository.to_string(),
+        name: name.to_string(),
+        installed_version: status.is_installed().then(|| version.to_string()),
+        available_version: Some(version.to_string()),
+        qualified_name: qualified_name.to_string(),
+        ..Default::default()
+    }
+}
+
+fn packages() -> Vec<PackageData> {
+    vec![
+        package("extra/vim", PackageStatus::UpToDate, "9.1.0-1"),
+        package("extra/gvim", PackageStatus::NotInstalled, "9.1.0-1"),
+        package("core/vi", PackageStatus::UpdateAvailable, "1:070224-6"),
+        package("extra/visual studio", PackageStatus::NotInstalled, "1.95"),
+        package("community/neovim", PackageStatus::UpToDate, "0.10.2-1"),
+    ]
+}
+
+/// Names of the packages matching `text`, with plain substring matching.
+fn search(text: &str) -> Vec<String> {
+    let query = Query::parse(text).unwrap();
+    packages()
+        .into_iter()
+        .filter(|package| {
+            query.matches(package, "Pacman", |name, text| {
+                name.to_lowercase().contains(&text.to_lowercase())
+            })
+        })
+        .map(|package| package.name)
+        .collect()
+}
+
+#[test]
+fn query_without_fields_matches_the_qualified_name() {
+    assert_eq!(search("vim"), ["vim", "gvim", "neovim"]);
+    assert_eq!(search("extra/vi"), ["vim", "visual studio"]);
+    assert_eq!(search(""), ["vim", "gvim", "vi", "visual studio", "neovim"]);
+}
+
+#[test]
+fn query_fields_combine_and_negate() {
+    assert_eq!(search("repo:extra installed:yes"), ["vim"]);
+    assert_eq!(search("updates:yes"), ["vi"]);
+    assert_eq!(search("vim -repo:extra"), ["neovim"]);
+    assert_eq!(search("name:vi -name:vim"), ["vi", "visual studio"]);
+    assert_eq!(
+        search("provider:pacman installed:no"),
+        ["gvim", "visual studio"]
+    );
+    assert!(search("provider:flatpak").is_empty());
+}
+
+#[test]
+fn query_quotes_keep_spaces() {
+    assert_eq!(search("\"visual studio\""), ["visual studio"]);
+    assert_eq!(search("name:\"visual studio\""), ["visual studio"]);
+    assert_eq!(search("repo:extra -\"visual studio\""), ["vim", "gvim"]);
+}
+
+#[test]
+fn query_matches_descriptions() {
+    let mut packages = packages();
+    packages[4].description = Some("Vim-fork focused on extensibility".to_string());
+    let query = Query::parse("description:FORK").unwrap();
+    let found: Vec<&str> = packages
+        .iter()
+        .filter(|package| query.matches(package, "Pacman", |_, _| false))
+        .map(|package| package.name.as_str())
+        .collect();
+    assert_eq!(found, ["neovim"]);
+    let query = Query::parse("extensibility").unwrap();
+    assert!(query.matches(&packages[4], "Pacman", |_, _| false));
+    assert!(!query.matches(&packages[0], "Pacman", |_, _| false));
+}
+
+#[test]
+fn query_compares_versions() {
+    assert_eq!(search("version:>=9.1"), ["vim", "gvim", "vi"]);
+    assert_eq!(search("version:<1.0"), ["neovim"]);
+    assert_eq!(search("version:=1.95"), ["visual studio"]);
+    assert_eq!(search("version:0.10"), ["neovim"]);
+}
+
+#[test]
+fn query_reports_syntax_errors_with_their_position() {
+    let error = |text: &str| Query::parse(text).unwrap_err();
+
+    let unknown = error("vim rpo:extra");
+    assert_eq!(unknown.position, 4);
+    assert!(unknown.message.starts_with("Unknown field rpo"));
+
+    assert_eq!(error("name:\"visual").message, "Missing closing quote");
+    assert_eq!(error("installed:maybe").position, 10);
+    assert_eq!(error("repo: vim").message, "Expected a value after repo:");
+    assert_eq!(
+        error("version:>=").message,
+        "Expected a version to compare to"
+    );
+    assert_eq!(error("vim - gvim").position, 4);
+}
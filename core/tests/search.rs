@@ -0,0 +1,149 @@
+use package_manager_core::{
+    package::{PackageData, PackageStatus},
+    query::Query,
+    search::{self, MatchKind, SearchIndex},
+};
+
+fn index(qualified_names: &[&str]) -> SearchIndex {
+    SearchIndex::new(
+        qualified_names
+            .iter()
+            .map(|qualified_name| {
+                let (repository, name) = qualified_name.split_once('/').unwrap();
+                PackageData {
+                    status: if repository == "local" {
+                        PackageStatus::UpToDate
+                    } else {
+                        PackageStatus::NotInstalled
+                    },
+                    repository: repository.to_string(),
+                    name: name.to_string(),
+                    installed_version: None,
+                    available_version: Some("1.0".to_string()),
+                    qualified_name: qualified_name.to_string(),
+                    ..Default::default()
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Names of the packages found in `indexes`, best first.
+fn find(indexes: &[(&str, &SearchIndex)], text: &str, fuzzy: Option<f32>) -> Vec<String> {
+    let query = Query::parse(text).unwrap();
+    search::find(indexes.iter().copied(), &query, fuzzy)
+        .into_iter()
+        .map(|(index, package)| indexes[index].1.packages()[package].name.clone())
+        .collect()
+}
+
+#[test]
+fn search_ranks_exact_then_prefix_then_substring() {
+    let index = index(&[
+        "extra/gvim",
+        "extra/vim-airline",
+        "aur/neovim",
+        "extra/vim",
+        "aur/emacs",
+    ]);
+    assert_eq!(
+        find(&[("Pacman", &index)], "vim", None),
+        ["vim", "vim-airline", "gvim", "neovim"]
+    );
+
+    let kinds: Vec<MatchKind> = index
+        .search(&["VIM"], None)
+        .iter()
+        .map(|hit| hit.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            MatchKind::Substring,
+            MatchKind::Prefix,
+            MatchKind::Substring,
+            MatchKind::Exact
+        ]
+    );
+}
+
+#[test]
+fn search_keeps_the_order_without_terms() {
+    let index = index(&["extra/zsh", "local/bash", "extra/fish"]);
+    assert_eq!(
+        find(&[("Pacman", &index)], "", None),
+        ["zsh", "bash", "fish"]
+    );
+    assert_eq!(
+        find(&[("Pacman", &index)], "installed:no", None),
+        ["zsh", "fish"]
+    );
+}
+
+#[test]
+fn search_matches_short_terms_and_every_term() {
+    let index = index(&["extra/git", "extra/gitg", "extra/tig", "extra/lazygit"]);
+    assert_eq!(
+        find(&[("Pacman", &index)], "g", None),
+        ["git", "gitg", "tig", "lazygit"]
+    );
+    assert_eq!(find(&[("Pacman", &index)], "git lazy", None), ["lazygit"]);
+    assert_eq!(
+        find(&[("Pacman", &index)], "git -gitg", None),
+        ["git", "lazygit"]
+    );
+    assert!(find(&[("Pacman", &index)], "mercurial", None).is_empty());
+}
+
+#[test]
+fn search_fuzzy_finds_misspelled_names_above_the_threshold() {
+    let index = index(&["extra/firewalld", "extra/thunderbird", "extra/firefox"]);
+    assert!(find(&[("Pacman", &index)], "firefx", None).is_empty());
+    assert_eq!(
+        find(&[("Pacman", &index)], "firefx", Some(0.3)),
+        ["firefox", "firewalld"]
+    );
+    assert_eq!(
+        find(&[("Pacman", &index)], "firefx", Some(0.5)),
+        ["firefox"]
+    );
+}
+
+#[test]
+fn search_merges_the_providers_by_rank() {
+    let pacman = index(&["extra/inkscape", "extra/gimp"]);
+    let flatpak = index(&["flathub/org.gimp.GIMP", "flathub/gimp"]);
+    let indexes = [("Pacman", &pacman), ("Flatpak", &flatpak)];
+    assert_eq!(
+        find(&indexes, "gimp", None),
+        ["gimp", "gimp", "org.gimp.GIMP"]
+    );
+    assert_eq!(
+        find(&indexes, "gimp provider:flatpak", None),
+        ["gimp", "org.gimp.GIMP"]
+    );
+}
+
+#[test]
+fn search_finds_descriptions_after_names() {
+    let mut packages = index(&["extra/vim", "extra/editorconfig-core-c", "extra/nano"])
+        .packages()
+        .to_vec();
+    packages[0].description = Some("Vi Improved, a highly configurable text editor".to_string());
+    packages[2].description = Some("Pico editor clone with enhancements".to_string());
+    let index = SearchIndex::new(packages);
+
+    assert_eq!(
+        find(&[("Pacman", &index)], "editor", None),
+        ["editorconfig-core-c", "vim", "nano"]
+    );
+    assert_eq!(
+        index.search(&["editor"], None)[0].kind,
+        MatchKind::Description
+    );
+    assert_eq!(find(&[("Pacman", &index)], "editor pico", None), ["nano"]);
+    assert_eq!(
+        find(&[("Pacman", &index)], "description:editor", None),
+        ["vim", "nano"]
+    );
+}
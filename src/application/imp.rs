@@ -1,9 +1,16 @@
-use crate::window::Window;
+use std::cell::RefCell;
+
+use crate::{dbus, window::Window};
 
 use super::*;
 use gtk::glib;
+/// How long a D-Bus activated instance waits for calls before exiting.
+const SERVICE_TIMEOUT_MS: u32 = 60_000;
+
 #[derive(Debug, Default)]
-pub struct PackageManagerApplication {}
+pub struct PackageManagerApplication {
+    dbus_registration: RefCell<Option<gio::RegistrationId>>,
+}
 
 #[glib::object_subclass]
 impl ObjectSubclass for PackageManagerApplication {
@@ -32,6 +39,36 @@ impl ApplicationImpl for PackageManagerApplication {
         };
         window.present();
     }
+
+    fn startup(&self) {
+        self.parent_startup();
+        let application = self.obj();
+        // Started by D-Bus activation, stays around a while for the next calls
+        if application
+            .flags()
+            .contains(gio::ApplicationFlags::IS_SERVICE)
+        {
+            application.set_inactivity_timeout(SERVICE_TIMEOUT_MS);
+        }
+    }
+
+    fn dbus_register(
+        &self,
+        connection: &gio::DBusConnection,
+        object_path: &str,
+    ) -> Result<(), glib::Error> {
+        self.parent_dbus_register(connection, object_path)?;
+        let registration = dbus::register(&*self.obj(), connection, object_path)?;
+        self.dbus_registration.replace(Some(registration));
+        Ok(())
+    }
+
+    fn dbus_unregister(&self, connection: &gio::DBusConnection, object_path: &str) {
+        if let Some(registration) = self.dbus_registration.take() {
+            let _ = connection.unregister_object(registration);
+        }
+        self.parent_dbus_unregister(connection, object_path);
+    }
 }
 
 impl GtkApplicationImpl for PackageManagerApplication {}
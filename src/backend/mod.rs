@@ -1,7 +1,7 @@
-pub mod api;
-pub mod command;
+pub use package_manager_core::{
+    ansi, catalog, command, details, error, history, package, privilege, provider, query, search,
+    settings,
+};
+
 pub mod package_object;
-pub mod provider;
-pub mod providers_impl;
-pub mod settings;
-pub mod utils;
+pub mod provider_object;
@@ -10,12 +10,54 @@ use super::PackageData;
 #[derive(Properties, Default)]
 #[properties(wrapper_type = super::PackageObject)]
 pub struct PackageObject {
-    #[property(name = "installed", get, set, type = bool, member = installed)]
+    #[property(name = "installed", get = Self::installed, type = bool)]
+    #[property(name = "status", get = Self::status, type = String)]
     #[property(name = "repository", get, set, type = String, member = repository)]
     #[property(name = "name", get, set, type = String, member = name)]
-    #[property(name = "version", get, set, type = String, member = version)]
+    #[property(name = "installedVersion", get, set, type = Option<String>, member = installed_version)]
+    #[property(name = "availableVersion", get, set, type = Option<String>, member = available_version)]
     #[property(name = "qualifiedName", get, set, type = String, member = qualified_name)]
+    #[property(name = "description", get, type = Option<String>, member = description)]
+    #[property(name = "license", get = Self::license, type = String)]
+    #[property(name = "url", get, type = Option<String>, member = url)]
+    #[property(name = "size", get = Self::size, type = u64)]
+    #[property(name = "installDate", get = Self::install_date, type = i64)]
+    #[property(name = "popularity", get = Self::popularity, type = f64)]
     pub data: RefCell<PackageData>,
+    /// Name of the provider the package comes from.
+    #[property(get, set)]
+    pub provider: RefCell<String>,
+}
+
+impl PackageObject {
+    fn installed(&self) -> bool {
+        self.data.borrow().installed()
+    }
+
+    fn status(&self) -> String {
+        self.data.borrow().status.to_string()
+    }
+
+    fn license(&self) -> String {
+        self.data.borrow().licenses.join(", ")
+    }
+
+    /// Bytes on disk once installed, else to download, 0 when unknown.
+    fn size(&self) -> u64 {
+        let data = self.data.borrow();
+        data.installed_size
+            .or(data.download_size)
+            .unwrap_or_default()
+    }
+
+    /// 0 when not installed or unknown.
+    fn install_date(&self) -> i64 {
+        self.data.borrow().install_date.unwrap_or_default()
+    }
+
+    fn popularity(&self) -> f64 {
+        self.data.borrow().popularity.unwrap_or_default()
+    }
 }
 
 #[glib::object_subclass]
@@ -36,4 +78,4 @@ impl ObjectImpl for PackageObject {
     fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
         self.derived_property(id, pspec)
     }
-}
\ No newline at end of file
+}
@@ -2,45 +2,22 @@ mod imp;
 
 use glib::Object;
 use gtk::glib;
+use gtk::subclass::prelude::*;
+
+use super::package::{PackageData, PackageStatus};
 
 glib::wrapper! {
     pub struct PackageObject(ObjectSubclass<imp::PackageObject>);
 }
 
 impl PackageObject {
-    pub fn new(
-        installed: bool,
-        repository: String,
-        name: String,
-        version: String,
-        qualified_name: String,
-    ) -> Self {
-        Object::builder()
-            .property("installed", installed)
-            .property("repository", repository)
-            .property("name", name)
-            .property("version", version)
-            .property("qualifiedName", qualified_name)
-            .build()
+    pub fn new(provider: &str, data: PackageData) -> Self {
+        let object: Self = Object::builder().property("provider", provider).build();
+        object.imp().data.replace(data);
+        object
     }
-}
-#[derive(Default, Clone, Debug)]
-pub struct PackageData {
-    pub installed: bool,
-    pub repository: String,
-    pub name: String,
-    pub version: String,
-    pub qualified_name: String,
-}
 
-impl PackageData {
-    pub fn cast(&self) -> PackageObject {
-        PackageObject::new(
-            self.installed,
-            self.repository.to_owned(),
-            self.name.to_owned(),
-            self.version.to_owned(),
-            self.qualified_name.to_owned(),
-        )
+    pub fn status_kind(&self) -> PackageStatus {
+        self.imp().data.borrow().status
     }
 }
@@ -0,0 +1,43 @@
+use std::cell::{Cell, RefCell};
+
+use glib::{ParamSpec, Properties, Value};
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+#[derive(Properties, Default)]
+#[properties(wrapper_type = super::ProviderObject)]
+pub struct ProviderObject {
+    #[property(get, set)]
+    pub name: RefCell<String>,
+    #[property(get)]
+    pub loading: Cell<bool>,
+    #[property(get)]
+    pub failed: Cell<bool>,
+    /// Failed because the network is down, so only the catalog can be shown.
+    #[property(get)]
+    pub offline: Cell<bool>,
+    /// Why the packages could not be loaded, empty unless `failed`.
+    #[property(get)]
+    pub error: RefCell<String>,
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for ProviderObject {
+    const NAME: &'static str = "ProviderObject";
+    type Type = super::ProviderObject;
+}
+
+impl ObjectImpl for ProviderObject {
+    fn properties() -> &'static [ParamSpec] {
+        Self::derived_properties()
+    }
+
+    fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
+        self.derived_set_property(id, value, pspec)
+    }
+
+    fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+        self.derived_property(id, pspec)
+    }
+}
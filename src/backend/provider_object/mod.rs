@@ -0,0 +1,43 @@
+mod imp;
+
+use glib::Object;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+glib::wrapper! {
+    pub struct ProviderObject(ObjectSubclass<imp::ProviderObject>);
+}
+
+/// Where the packages of a provider are at, shown next to its name in the dropdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderStatus {
+    Loading,
+    Ready,
+    /// Failed to reach the network.
+    Offline(String),
+    Failed(String),
+}
+
+impl ProviderObject {
+    pub fn new(name: &str) -> Self {
+        Object::builder().property("name", name).build()
+    }
+
+    pub fn set_status(&self, status: ProviderStatus) {
+        let imp = self.imp();
+        let (loading, offline, error) = match status {
+            ProviderStatus::Loading => (true, false, None),
+            ProviderStatus::Ready => (false, false, None),
+            ProviderStatus::Offline(error) => (false, true, Some(error)),
+            ProviderStatus::Failed(error) => (false, false, Some(error)),
+        };
+        imp.loading.set(loading);
+        imp.offline.set(offline);
+        imp.failed.set(error.is_some());
+        imp.error.replace(error.unwrap_or_default());
+        for property in ["loading", "offline", "failed", "error"] {
+            self.notify(property);
+        }
+    }
+}
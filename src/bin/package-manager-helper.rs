@@ -0,0 +1,35 @@
+//! Runs a package manager as root on behalf of the app, started through `pkexec` so polkit
+//! authorises it (see `data/org.caioxcezar.packagemanager.policy`).
+
+use std::process::exit;
+
+/// Only these programs can be run, always from their absolute path.
+const ALLOWED: [(&str, &str); 2] = [("pacman", "/usr/bin/pacman"), ("dnf", "/usr/bin/dnf")];
+
+#[cfg(unix)]
+fn main() {
+    use std::{env, os::unix::process::CommandExt, process::Command};
+
+    let mut args = env::args().skip(1);
+    let Some(program) = args.next() else {
+        eprintln!("Usage: package-manager-helper <pacman|dnf> [arguments]");
+        exit(2);
+    };
+    let Some((_, path)) = ALLOWED.iter().find(|(name, _)| *name == program) else {
+        eprintln!("{program} is not allowed");
+        exit(2);
+    };
+
+    let err = Command::new(path)
+        .args(args)
+        .env_remove("LD_PRELOAD")
+        .exec();
+    eprintln!("Failed to run {program}: {err}");
+    exit(1);
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("package-manager-helper is only supported on unix");
+    exit(1);
+}
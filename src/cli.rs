@@ -0,0 +1,525 @@
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::process;
+use std::sync::Mutex;
+use std::thread;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use gtk::glib;
+use secstr::{SecStr, SecVec};
+use serde::Serialize;
+
+use crate::{
+    backend::{
+        command::{Canceller, CommandStream, Line, Outcome},
+        error::ProviderError,
+        package::PackageData,
+        privilege::{Privilege, PASSWORD_ATTEMPTS},
+        provider::{Change, Operation, ProviderKind},
+    },
+    constants,
+};
+
+const EXIT_FAILURE: u8 = 1;
+const EXIT_NOT_FOUND: u8 = 3;
+const EXIT_AUTH_FAILED: u8 = 4;
+const EXIT_CANCELLED: u8 = 130;
+
+const EXIT_CODES: &str = "Exit codes:
+  0    success
+  1    the package manager failed
+  2    invalid arguments
+  3    provider or package not found
+  4    authentication failed
+  130  cancelled
+
+Run without arguments to open the window.";
+
+#[derive(Parser)]
+#[command(
+    name = "package-manager",
+    version = constants::VERSION,
+    about = "Manage packages of every package manager installed",
+    after_help = EXIT_CODES
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List the providers available on this system
+    Providers {
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the packages of a provider, or of every provider
+    List {
+        #[arg(short, long)]
+        provider: Option<String>,
+        /// Only show installed packages
+        #[arg(long)]
+        installed: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Search packages by name
+    Search {
+        query: String,
+        #[arg(short, long)]
+        provider: Option<String>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the details of a package
+    Info {
+        package: String,
+        #[arg(short, long)]
+        provider: String,
+    },
+    /// Install packages in a single transaction
+    Install {
+        #[arg(required = true)]
+        packages: Vec<String>,
+        #[arg(short, long)]
+        provider: String,
+    },
+    /// Remove packages in a single transaction
+    Remove {
+        #[arg(required = true)]
+        packages: Vec<String>,
+        #[arg(short, long)]
+        provider: String,
+    },
+    /// Upgrade the given packages
+    Update {
+        #[arg(required = true)]
+        packages: Vec<String>,
+        #[arg(short, long)]
+        provider: String,
+    },
+    /// Upgrade everything of a provider, or of every provider
+    UpdateAll {
+        #[arg(short, long)]
+        provider: Option<String>,
+    },
+}
+
+/// Arguments that start the command line instead of the window.
+const COMMANDS: [&str; 13] = [
+    "providers",
+    "list",
+    "search",
+    "info",
+    "install",
+    "remove",
+    "update",
+    "update-all",
+    "help",
+    "-h",
+    "--help",
+    "-V",
+    "--version",
+];
+
+#[derive(Debug)]
+enum CliError {
+    NotFound(String),
+    AuthFailed,
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::NotFound(what) => write!(f, "{what} not found"),
+            CliError::AuthFailed => write!(f, "Authentication failed"),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    provider: String,
+    #[serde(flatten)]
+    package: &'a PackageData,
+}
+
+/// Runs the command line when the first argument is one of its commands, `None` opens the window.
+pub fn run() -> Option<glib::ExitCode> {
+    let first = std::env::args().nth(1)?;
+    if !COMMANDS.contains(&first.as_str()) {
+        return None;
+    }
+    // Exits with 2 on invalid arguments
+    let cli = Cli::parse();
+    if let Err(err) = cancel_on_interrupt() {
+        eprintln!("Failed to handle Ctrl-C: {err}");
+    }
+    let code = match execute(cli.command) {
+        Ok(Outcome::Success) => 0,
+        Ok(Outcome::Cancelled) => EXIT_CANCELLED,
+        Err(err) => {
+            eprintln!("Error: {err:?}");
+            if let Some(hint) = err
+                .downcast_ref::<ProviderError>()
+                .and_then(ProviderError::hint)
+            {
+                eprintln!("{hint}");
+            }
+            exit_code(&err)
+        }
+    };
+    Some(glib::ExitCode::from(code))
+}
+
+fn execute(command: Command) -> Result<Outcome> {
+    match command {
+        Command::Providers { json } => {
+            let providers = ProviderKind::available_providers();
+            if json {
+                let providers: Vec<serde_json::Value> = providers
+                    .iter()
+                    .map(|provider| {
+                        serde_json::json!({
+                            "name": provider.name(),
+                            "root_required": provider.is_root_required(),
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&providers)?);
+            } else {
+                for provider in providers {
+                    println!("{}", provider.name());
+                }
+            }
+        }
+        Command::List {
+            provider,
+            installed,
+            json,
+        } => {
+            let providers = load(providers(provider.as_deref())?)?;
+            print_packages(&providers, json, |package| {
+                !installed || package.installed()
+            })?;
+        }
+        Command::Search {
+            query,
+            provider,
+            json,
+        } => {
+            let providers = load(providers(provider.as_deref())?)?;
+            let query = query.to_lowercase();
+            print_packages(&providers, json, |package| {
+                package.name.to_lowercase().contains(&query)
+                    || package.qualified_name.to_lowercase().contains(&query)
+            })?;
+        }
+        Command::Info { package, provider } => {
+            let provider = loaded_provider(&provider)?;
+            let package = resolve(&provider, &package)?;
+            println!("{}", provider.package_info(package)?);
+        }
+        Command::Install { packages, provider } => {
+            return apply(&provider, &packages, Operation::Install);
+        }
+        Command::Remove { packages, provider } => {
+            return apply(&provider, &packages, Operation::Remove);
+        }
+        Command::Update { packages, provider } => {
+            let provider = loaded_provider(&provider)?;
+            let packages = packages
+                .iter()
+                .map(|package| resolve(&provider, package))
+                .collect::<Result<Vec<String>>>()?;
+            let password = password(&[&provider])?;
+            return stream_output(provider.update_selected(password, packages)?);
+        }
+        Command::UpdateAll { provider } => {
+            let providers = providers(provider.as_deref())?;
+            let password = password(&providers.iter().collect::<Vec<_>>())?;
+            // The other providers are still updated, the first failure sets the exit code
+            let mut failure: Option<anyhow::Error> = None;
+            for provider in &providers {
+                println!("::: {} :::", provider.name());
+                let result = provider
+                    .update(password.clone())
+                    .map_err(Into::into)
+                    .and_then(stream_output);
+                match result {
+                    Ok(Outcome::Success) => {}
+                    Ok(Outcome::Cancelled) => return failure.map_or(Ok(Outcome::Cancelled), Err),
+                    Err(err) if failure.is_some() => eprintln!("Error: {err:?}"),
+                    Err(err) => failure = Some(err),
+                }
+            }
+            return failure.map_or(Ok(Outcome::Success), Err);
+        }
+    }
+    Ok(Outcome::Success)
+}
+
+/// The available providers, only the one named `name` when given.
+fn providers(name: Option<&str>) -> Result<Vec<ProviderKind>> {
+    let providers = ProviderKind::available_providers();
+    let Some(name) = name else {
+        return Ok(providers);
+    };
+    let normalize = |value: &str| value.replace(' ', "").to_lowercase();
+    let providers: Vec<ProviderKind> = providers
+        .into_iter()
+        .filter(|provider| normalize(&provider.name()) == normalize(name))
+        .collect();
+    if providers.is_empty() {
+        return Err(CliError::NotFound(format!("Provider {name}")).into());
+    }
+    Ok(providers)
+}
+
+fn load(mut providers: Vec<ProviderKind>) -> Result<Vec<ProviderKind>> {
+    if let [provider] = providers.as_mut_slice() {
+        provider.update_packages()?;
+        return Ok(providers);
+    }
+    // One broken provider doesn't hide the others
+    Ok(providers
+        .into_iter()
+        .filter_map(|mut provider| match provider.update_packages() {
+            Ok(()) => Some(provider),
+            Err(err) => {
+                eprintln!("Failed to load {}: {err:?}", provider.name());
+                None
+            }
+        })
+        .collect())
+}
+
+fn loaded_provider(name: &str) -> Result<ProviderKind> {
+    load(providers(Some(name))?)?
+        .pop()
+        .ok_or_else(|| CliError::NotFound(format!("Provider {name}")).into())
+}
+
+/// Finds the qualified name of a package given by qualified name, name or a part of the
+/// qualified name such as a flatpak application id.
+fn resolve(provider: &ProviderKind, query: &str) -> Result<String> {
+    let packages = provider.packages();
+    packages
+        .iter()
+        .find(|package| package.qualified_name == query)
+        .or_else(|| {
+            packages
+                .iter()
+                .find(|package| package.name.eq_ignore_ascii_case(query))
+        })
+        .or_else(|| {
+            packages.iter().find(|package| {
+                package
+                    .qualified_name
+                    .split([' ', '/'])
+                    .any(|part| part == query)
+            })
+        })
+        .map(|package| package.qualified_name.clone())
+        .ok_or_else(|| CliError::NotFound(format!("Package {query}")).into())
+}
+
+fn apply(provider: &str, packages: &[String], operation: Operation) -> Result<Outcome> {
+    let provider = loaded_provider(provider)?;
+    let changes = packages
+        .iter()
+        .map(|package| {
+            Ok(Change {
+                operation,
+                package: resolve(&provider, package)?,
+            })
+        })
+        .collect::<Result<Vec<Change>>>()?;
+    let password = password(&[&provider])?;
+    stream_output(provider.execute(password, changes)?)
+}
+
+fn print_packages(
+    providers: &[ProviderKind],
+    json: bool,
+    filter: impl Fn(&PackageData) -> bool,
+) -> Result<()> {
+    let packages: Vec<(String, PackageData)> = providers
+        .iter()
+        .flat_map(|provider| {
+            let name = provider.name();
+            provider
+                .packages()
+                .into_iter()
+                .filter(|package| filter(package))
+                .map(move |package| (name.clone(), package))
+        })
+        .collect();
+
+    if json {
+        let entries: Vec<Entry> = packages
+            .iter()
+            .map(|(provider, package)| Entry {
+                provider: provider.clone(),
+                package,
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    let rows: Vec<[String; 6]> = packages
+        .into_iter()
+        .map(|(provider, package)| {
+            [
+                provider,
+                package.qualified_name,
+                package.status.to_string(),
+                package.installed_version.unwrap_or_default(),
+                package.available_version.unwrap_or_default(),
+                package.repository,
+            ]
+        })
+        .collect();
+    let mut widths = [0; 6];
+    for row in &rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+    let mut stdout = io::stdout().lock();
+    for row in rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(value, width)| format!("{value:width$}"))
+            .collect();
+        writeln!(stdout, "{}", line.join("  ").trim_end())?;
+    }
+    Ok(())
+}
+
+/// Asks for the sudo password on the terminal when one of the providers needs it.
+fn password(providers: &[&ProviderKind]) -> Result<Option<SecVec<u8>>> {
+    let privilege = Privilege::current();
+    if !privilege.needs_password() || !providers.iter().any(|p| p.is_root_required()) {
+        return Ok(None);
+    }
+    for _ in 0..PASSWORD_ATTEMPTS {
+        let password = SecStr::from(rpassword::prompt_password("[sudo] password: ")?);
+        if privilege.verify(password.clone()).is_ok() {
+            return Ok(Some(password));
+        }
+        eprintln!("Sorry, try again.");
+    }
+    Err(CliError::AuthFailed.into())
+}
+
+/// Prints the output of the command, asking the terminal when it prompts for an answer.
+fn stream_output(mut stream: CommandStream) -> Result<Outcome> {
+    set_running(Some(stream.canceller()));
+    let outcome = print_stream(&mut stream);
+    set_running(None);
+    outcome
+}
+
+fn print_stream(stream: &mut CommandStream) -> Result<Outcome> {
+    let responder = stream.responder();
+    let canceller = stream.canceller();
+    let terminal = io::stdout().is_terminal();
+    let mut stdout = io::stdout();
+    let mut progress = false;
+
+    for line in &mut *stream {
+        if progress && !matches!(line, Line::Progress(_)) {
+            write!(stdout, "\r\x1b[K")?;
+        }
+        progress = false;
+        match line {
+            Line::Stdout(text) => writeln!(stdout, "{text}")?,
+            Line::Stderr(text) => eprintln!("{text}"),
+            Line::Progress(text) if terminal => {
+                write!(stdout, "\r\x1b[K{text}")?;
+                stdout.flush()?;
+                progress = true;
+            }
+            Line::Progress(_) => {}
+            Line::Prompt(text) => {
+                write!(stdout, "{text} ")?;
+                stdout.flush()?;
+                let mut answer = String::new();
+                io::stdin().read_line(&mut answer)?;
+                // Cancelled while waiting for the answer, the command is already gone
+                if !canceller.is_cancelled() {
+                    responder.answer(answer.trim_end())?;
+                }
+            }
+        }
+    }
+
+    Ok(stream.close().map_err(ProviderError::from)?)
+}
+
+/// The command [`stream_output`] is running, cancelled on Ctrl-C.
+static RUNNING: Mutex<Option<Canceller>> = Mutex::new(None);
+
+fn set_running(canceller: Option<Canceller>) {
+    if let Ok(mut running) = RUNNING.lock() {
+        *running = canceller;
+    }
+}
+
+/// Forwards Ctrl-C to the running command. Commands run in their own process group, so the
+/// terminal's SIGINT only reaches the CLI and would leave them orphaned.
+#[cfg(unix)]
+fn cancel_on_interrupt() -> io::Result<()> {
+    use std::io::Read;
+    use std::os::fd::IntoRawFd;
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    static WAKE: AtomicI32 = AtomicI32::new(-1);
+
+    extern "C" fn on_interrupt(_: libc::c_int) {
+        // Only async-signal-safe calls here, the thread below does the cancelling
+        let byte = 0u8;
+        unsafe { libc::write(WAKE.load(Ordering::Relaxed), (&raw const byte).cast(), 1) };
+    }
+
+    let (mut reader, writer) = io::pipe()?;
+    WAKE.store(writer.into_raw_fd(), Ordering::Relaxed);
+    thread::spawn(move || {
+        let mut byte = [0];
+        while reader.read_exact(&mut byte).is_ok() {
+            match RUNNING.lock().ok().and_then(|running| running.clone()) {
+                Some(canceller) => canceller.cancel(),
+                None => process::exit(EXIT_CANCELLED.into()),
+            }
+        }
+    });
+    let handler = on_interrupt as extern "C" fn(libc::c_int);
+    unsafe { libc::signal(libc::SIGINT, handler as libc::sighandler_t) };
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn cancel_on_interrupt() -> io::Result<()> {
+    Ok(())
+}
+
+fn exit_code(err: &anyhow::Error) -> u8 {
+    match err.downcast_ref::<CliError>() {
+        Some(CliError::NotFound(_)) => return EXIT_NOT_FOUND,
+        Some(CliError::AuthFailed) => return EXIT_AUTH_FAILED,
+        None => {}
+    }
+    match err.downcast_ref::<ProviderError>() {
+        Some(ProviderError::Unavailable(_) | ProviderError::PackageNotFound(_)) => EXIT_NOT_FOUND,
+        Some(ProviderError::AuthFailed) => EXIT_AUTH_FAILED,
+        Some(ProviderError::Cancelled) => EXIT_CANCELLED,
+        _ => EXIT_FAILURE,
+    }
+}
@@ -1,2 +1,2 @@
-pub static APP_ID: &str = "org.caioxcezar.packagemanager";
+pub use package_manager_core::APP_ID;
 pub static VERSION: &str = "1.12.8";
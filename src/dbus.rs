@@ -0,0 +1,461 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+    thread::spawn,
+};
+
+use gtk::{
+    gio::{self, prelude::*, DBusConnection, DBusError, RegistrationId},
+    glib::{self, prelude::ToVariant},
+};
+
+use crate::backend::{
+    command::{Canceller, CommandStream, Line, Outcome, Responder},
+    error::{ProviderError, Result},
+    package::PackageData,
+    privilege::Privilege,
+    provider::{Change, Operation, ProviderKind},
+};
+
+const INTERFACE: &str = "org.caioxcezar.packagemanager.Packages";
+
+const INTERFACE_XML: &str = r#"
+<node>
+  <interface name="org.caioxcezar.packagemanager.Packages">
+    <method name="ListProviders">
+      <arg type="a(sb)" name="providers" direction="out"/>
+    </method>
+    <method name="Search">
+      <arg type="s" name="provider" direction="in"/>
+      <arg type="s" name="query" direction="in"/>
+      <arg type="a(ssssss)" name="packages" direction="out"/>
+    </method>
+    <method name="GetInfo">
+      <arg type="s" name="provider" direction="in"/>
+      <arg type="s" name="package" direction="in"/>
+      <arg type="s" name="info" direction="out"/>
+    </method>
+    <method name="ListUpdates">
+      <arg type="s" name="provider" direction="in"/>
+      <arg type="a(ssssss)" name="packages" direction="out"/>
+    </method>
+    <method name="Install">
+      <arg type="s" name="provider" direction="in"/>
+      <arg type="as" name="packages" direction="in"/>
+      <arg type="u" name="job" direction="out"/>
+    </method>
+    <method name="Remove">
+      <arg type="s" name="provider" direction="in"/>
+      <arg type="as" name="packages" direction="in"/>
+      <arg type="u" name="job" direction="out"/>
+    </method>
+    <method name="Update">
+      <arg type="s" name="provider" direction="in"/>
+      <arg type="as" name="packages" direction="in"/>
+      <arg type="u" name="job" direction="out"/>
+    </method>
+    <method name="Answer">
+      <arg type="u" name="job" direction="in"/>
+      <arg type="s" name="answer" direction="in"/>
+    </method>
+    <method name="Cancel">
+      <arg type="u" name="job" direction="in"/>
+    </method>
+    <signal name="JobOutput">
+      <arg type="u" name="job"/>
+      <arg type="s" name="stream"/>
+      <arg type="s" name="text"/>
+    </signal>
+    <signal name="JobPrompt">
+      <arg type="u" name="job"/>
+      <arg type="s" name="prompt"/>
+    </signal>
+    <signal name="JobProgress">
+      <arg type="u" name="job"/>
+      <arg type="d" name="fraction"/>
+      <arg type="s" name="label"/>
+    </signal>
+    <signal name="JobFinished">
+      <arg type="u" name="job"/>
+      <arg type="s" name="outcome"/>
+      <arg type="s" name="message"/>
+    </signal>
+  </interface>
+</node>
+"#;
+
+/// Packages are sent as `(qualified name, name, status, installed version, available version,
+/// repository)`, with missing versions as empty strings.
+type PackageRow = (String, String, String, String, String, String);
+
+#[derive(Debug)]
+enum Method {
+    ListProviders,
+    Search {
+        provider: String,
+        query: String,
+    },
+    GetInfo {
+        provider: String,
+        package: String,
+    },
+    ListUpdates {
+        provider: String,
+    },
+    Install {
+        provider: String,
+        packages: Vec<String>,
+    },
+    Remove {
+        provider: String,
+        packages: Vec<String>,
+    },
+    Update {
+        provider: String,
+        packages: Vec<String>,
+    },
+    Answer {
+        job: u32,
+        answer: String,
+    },
+    Cancel {
+        job: u32,
+    },
+}
+
+impl DBusMethodCall for Method {
+    fn parse_call(
+        _obj_path: &str,
+        _interface: Option<&str>,
+        method: &str,
+        params: glib::Variant,
+    ) -> Result<Self, glib::Error> {
+        let call = match method {
+            "ListProviders" => Some(Method::ListProviders),
+            "Search" => params
+                .get::<(String, String)>()
+                .map(|(provider, query)| Method::Search { provider, query }),
+            "GetInfo" => params
+                .get::<(String, String)>()
+                .map(|(provider, package)| Method::GetInfo { provider, package }),
+            "ListUpdates" => params
+                .get::<(String,)>()
+                .map(|(provider,)| Method::ListUpdates { provider }),
+            "Install" => params
+                .get::<(String, Vec<String>)>()
+                .map(|(provider, packages)| Method::Install { provider, packages }),
+            "Remove" => params
+                .get::<(String, Vec<String>)>()
+                .map(|(provider, packages)| Method::Remove { provider, packages }),
+            "Update" => params
+                .get::<(String, Vec<String>)>()
+                .map(|(provider, packages)| Method::Update { provider, packages }),
+            "Answer" => params
+                .get::<(u32, String)>()
+                .map(|(job, answer)| Method::Answer { job, answer }),
+            "Cancel" => params.get::<(u32,)>().map(|(job,)| Method::Cancel { job }),
+            _ => None,
+        };
+        call.ok_or_else(|| {
+            glib::Error::new(DBusError::InvalidArgs, &format!("Invalid call to {method}"))
+        })
+    }
+}
+
+enum Event {
+    Line(Line),
+    Closed(Result<Outcome>),
+}
+
+struct Job {
+    canceller: Canceller,
+    responder: Responder,
+    _hold: Option<gio::ApplicationHoldGuard>,
+}
+
+/// Answers the calls of the interface, sharing the process with the window.
+struct Service {
+    application: glib::WeakRef<gio::Application>,
+    connection: DBusConnection,
+    object_path: String,
+    providers: RefCell<HashMap<String, ProviderKind>>,
+    jobs: RefCell<HashMap<u32, Job>>,
+    next_job: Cell<u32>,
+}
+
+/// Exports the interface next to the actions of the application.
+pub fn register(
+    application: &impl IsA<gio::Application>,
+    connection: &DBusConnection,
+    object_path: &str,
+) -> Result<RegistrationId, glib::Error> {
+    let interface = gio::DBusNodeInfo::for_xml(INTERFACE_XML)?
+        .lookup_interface(INTERFACE)
+        .expect("Interface missing from its own XML");
+    let service = Rc::new(Service {
+        application: application.upcast_ref::<gio::Application>().downgrade(),
+        connection: connection.clone(),
+        object_path: object_path.to_string(),
+        providers: RefCell::default(),
+        jobs: RefCell::default(),
+        next_job: Cell::new(1),
+    });
+    connection
+        .register_object(object_path, &interface)
+        .typed_method_call::<Method>()
+        .invoke_and_return_future_local(move |_, _, call| {
+            let service = service.clone();
+            async move { service.handle(call).await }
+        })
+        .build()
+}
+
+impl Service {
+    async fn handle(self: Rc<Self>, call: Method) -> Result<Option<glib::Variant>, glib::Error> {
+        // Keeps a D-Bus activated service alive while providers load
+        let _hold = self.application.upgrade().map(|app| app.hold());
+        match call {
+            Method::ListProviders => {
+                let providers = background(|| {
+                    Ok(ProviderKind::available_providers()
+                        .iter()
+                        .map(|provider| (provider.name(), provider.is_root_required()))
+                        .collect::<Vec<(String, bool)>>())
+                })
+                .await?;
+                Ok(Some((providers,).to_variant()))
+            }
+            Method::Search { provider, query } => {
+                let query = query.to_lowercase();
+                let packages: Vec<PackageRow> = self
+                    .provider(&provider)
+                    .await?
+                    .packages()
+                    .into_iter()
+                    .filter(|package| {
+                        package.name.to_lowercase().contains(&query)
+                            || package.qualified_name.to_lowercase().contains(&query)
+                    })
+                    .map(row)
+                    .collect();
+                Ok(Some((packages,).to_variant()))
+            }
+            Method::GetInfo { provider, package } => {
+                let provider = self.provider(&provider).await?;
+                let info = background(move || provider.package_info(package)).await?;
+                Ok(Some((info,).to_variant()))
+            }
+            Method::ListUpdates { provider } => {
+                let provider = self.provider(&provider).await?;
+                let packages = background(move || provider.upgradable()).await?;
+                let packages: Vec<PackageRow> = packages.into_iter().map(row).collect();
+                Ok(Some((packages,).to_variant()))
+            }
+            Method::Install { provider, packages } => {
+                self.start(&provider, Operation::Install, packages).await
+            }
+            Method::Remove { provider, packages } => {
+                self.start(&provider, Operation::Remove, packages).await
+            }
+            Method::Update { provider, packages } => {
+                let kind = self.provider(&provider).await?;
+                check_password(&kind)?;
+                let stream = if packages.is_empty() {
+                    kind.update(None)
+                } else {
+                    kind.update_selected(None, packages)
+                };
+                let job = self.run(kind, stream.map_err(failed)?);
+                Ok(Some((job,).to_variant()))
+            }
+            Method::Answer { job, answer } => {
+                let jobs = self.jobs.borrow();
+                let job = jobs.get(&job).ok_or_else(|| unknown_job(job))?;
+                job.responder.answer(&answer).map_err(failed)?;
+                Ok(None)
+            }
+            Method::Cancel { job } => {
+                let jobs = self.jobs.borrow();
+                let job = jobs.get(&job).ok_or_else(|| unknown_job(job))?;
+                job.canceller.cancel();
+                Ok(None)
+            }
+        }
+    }
+
+    /// The provider named `name` with its packages loaded, reusing the last load.
+    async fn provider(&self, name: &str) -> Result<ProviderKind, glib::Error> {
+        if let Some(provider) = self.providers.borrow().get(name) {
+            return Ok(provider.clone());
+        }
+        let requested = name.to_string();
+        let provider = background(move || {
+            let mut provider = ProviderKind::available_providers()
+                .into_iter()
+                .find(|provider| provider.name().eq_ignore_ascii_case(&requested));
+            if let Some(provider) = &mut provider {
+                provider.update_packages()?;
+            }
+            Ok(provider)
+        })
+        .await?
+        .ok_or_else(|| {
+            glib::Error::new(
+                DBusError::InvalidArgs,
+                &format!("Provider {name} not found"),
+            )
+        })?;
+        self.providers
+            .borrow_mut()
+            .insert(name.to_string(), provider.clone());
+        Ok(provider)
+    }
+
+    async fn start(
+        self: &Rc<Self>,
+        provider: &str,
+        operation: Operation,
+        packages: Vec<String>,
+    ) -> Result<Option<glib::Variant>, glib::Error> {
+        let provider = self.provider(provider).await?;
+        check_password(&provider)?;
+        let changes = packages
+            .into_iter()
+            .map(|package| Change { operation, package })
+            .collect();
+        let stream = provider.execute(None, changes).map_err(failed)?;
+        let job = self.run(provider, stream);
+        Ok(Some((job,).to_variant()))
+    }
+
+    /// Streams the output of the command as signals, returning the id of the job.
+    fn run(self: &Rc<Self>, provider: ProviderKind, mut stream: CommandStream) -> u32 {
+        let id = self.next_job.get();
+        self.next_job.set(id + 1);
+        self.jobs.borrow_mut().insert(
+            id,
+            Job {
+                canceller: stream.canceller(),
+                responder: stream.responder(),
+                _hold: self.application.upgrade().map(|app| app.hold()),
+            },
+        );
+
+        let (sender, receiver) = async_channel::unbounded();
+        spawn(move || {
+            for line in &mut stream {
+                let _ = sender.send_blocking(Event::Line(line));
+            }
+            let _ =
+                sender.send_blocking(Event::Closed(stream.close().map_err(ProviderError::from)));
+        });
+
+        let service = self.clone();
+        let progress = provider.progress_format();
+        glib::spawn_future_local(async move {
+            while let Ok(event) = receiver.recv().await {
+                match event {
+                    Event::Line(line) => {
+                        if let Some(progress) = progress.parse(line.text()) {
+                            service.emit("JobProgress", (id, progress.fraction, progress.label()));
+                        }
+                        match line {
+                            Line::Stdout(text) => service.emit("JobOutput", (id, "stdout", text)),
+                            Line::Stderr(text) => service.emit("JobOutput", (id, "stderr", text)),
+                            Line::Progress(text) => {
+                                service.emit("JobOutput", (id, "progress", text))
+                            }
+                            Line::Prompt(text) => service.emit("JobPrompt", (id, text)),
+                        }
+                    }
+                    Event::Closed(result) => {
+                        let (outcome, message) = match result {
+                            Ok(Outcome::Success) => ("success", String::new()),
+                            Ok(Outcome::Cancelled) | Err(ProviderError::Cancelled) => {
+                                ("cancelled", String::new())
+                            }
+                            Err(ProviderError::CommandFailed {
+                                code: Some(code), ..
+                            }) => ("failed", format!("Exit code {code}")),
+                            Err(err) => ("failed", err.to_string()),
+                        };
+                        service.emit("JobFinished", (id, outcome, message));
+                    }
+                }
+            }
+            // The packages changed, so the next call loads them again
+            service.providers.borrow_mut().remove(&provider.name());
+            service.jobs.borrow_mut().remove(&id);
+        });
+        id
+    }
+
+    fn emit(&self, signal: &str, parameters: impl ToVariant) {
+        if let Err(err) = self.connection.emit_signal(
+            None,
+            &self.object_path,
+            INTERFACE,
+            signal,
+            Some(&parameters.to_variant()),
+        ) {
+            eprintln!("Failed to emit {signal}: {err}");
+        }
+    }
+}
+
+/// Runs blocking provider work on a thread, so the main loop keeps answering.
+async fn background<T, F>(work: F) -> Result<T, glib::Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let (sender, receiver) = async_channel::bounded(1);
+    spawn(move || {
+        let _ = sender.send_blocking(work());
+    });
+    receiver
+        .recv()
+        .await
+        .map_err(|err| failed(anyhow::Error::from(err)))?
+        .map_err(failed)
+}
+
+/// Passwords can only be typed in the window, so sudo without askpass can't be used here.
+fn check_password(provider: &ProviderKind) -> Result<(), glib::Error> {
+    if provider.is_root_required() && Privilege::current().needs_password() {
+        return Err(glib::Error::new(
+            DBusError::AccessDenied,
+            "A password is required, set up polkit or SUDO_ASKPASS to run this over D-Bus",
+        ));
+    }
+    Ok(())
+}
+
+fn row(package: PackageData) -> PackageRow {
+    (
+        package.qualified_name,
+        package.name,
+        package.status.to_string(),
+        package.installed_version.unwrap_or_default(),
+        package.available_version.unwrap_or_default(),
+        package.repository,
+    )
+}
+
+fn unknown_job(job: u32) -> glib::Error {
+    glib::Error::new(DBusError::InvalidArgs, &format!("Job {job} not found"))
+}
+
+/// Maps what went wrong to the closest standard D-Bus error.
+fn failed(err: impl Into<ProviderError>) -> glib::Error {
+    let err = err.into();
+    let code = match err {
+        ProviderError::AuthFailed => DBusError::AccessDenied,
+        ProviderError::Network(_) => DBusError::NoNetwork,
+        ProviderError::Unavailable(_) => DBusError::NotSupported,
+        ProviderError::PackageNotFound(_) => DBusError::InvalidArgs,
+        _ => DBusError::Failed,
+    };
+    glib::Error::new(code, &err.to_string())
+}
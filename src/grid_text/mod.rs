@@ -1,7 +1,17 @@
 mod imp;
 use gtk::glib;
+use gtk::prelude::WidgetExt;
 use gtk::subclass::prelude::*;
 
+use crate::backend::package::PackageStatus;
+
+const STATUS_CLASSES: [&str; 4] = [
+    "status-up-to-date",
+    "status-update",
+    "status-newer",
+    "status-foreign",
+];
+
 glib::wrapper! {
     pub struct GridText(ObjectSubclass<imp::GridText>)
         @extends gtk::Widget,
@@ -26,4 +36,19 @@ impl GridText {
     pub fn set_entry(&self, entry: &Entry) {
         self.imp().name.set_text(Some(&entry.name));
     }
+
+    pub fn set_status(&self, status: PackageStatus) {
+        let name = &self.imp().name;
+        for class in STATUS_CLASSES {
+            name.remove_css_class(class);
+        }
+        let class = match status {
+            PackageStatus::NotInstalled => return,
+            PackageStatus::UpToDate => STATUS_CLASSES[0],
+            PackageStatus::UpdateAvailable => STATUS_CLASSES[1],
+            PackageStatus::NewerThanRepo => STATUS_CLASSES[2],
+            PackageStatus::Foreign => STATUS_CLASSES[3],
+        };
+        name.add_css_class(class);
+    }
 }
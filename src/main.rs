@@ -2,7 +2,9 @@
 
 mod application;
 mod backend;
+mod cli;
 mod constants;
+mod dbus;
 mod grid_check;
 mod grid_text;
 mod messagebox;
@@ -13,6 +15,10 @@ use gdk::Display;
 use gtk::{gdk, gio, glib, prelude::*, CssProvider};
 
 fn main() -> glib::ExitCode {
+    if let Some(code) = cli::run() {
+        return code;
+    }
+
     // Register and include resources
     gio::resources_register_include!("package_manager.gresource")
         .expect("Failed to register resources.");
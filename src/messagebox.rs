@@ -4,7 +4,35 @@ use gtk::glib;
 use gtk::{prelude::*, AlertDialog};
 use secstr::{SecStr, SecVec};
 
-use crate::{backend::command, window::Window};
+use crate::{
+    backend::{
+        error::ProviderError,
+        privilege::{Privilege, PASSWORD_ATTEMPTS},
+    },
+    window::Window,
+};
+
+/// A way out offered by [`error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    Retry,
+    /// Loads the packages of the provider again.
+    Reload,
+    /// Looks again for the package managers installed.
+    FindProviders,
+    CopyDetails,
+}
+
+impl Recovery {
+    fn label(&self) -> &'static str {
+        match self {
+            Recovery::Retry => "Try Again",
+            Recovery::Reload => "Reload Packages",
+            Recovery::FindProviders => "Find Providers",
+            Recovery::CopyDetails => "Copy Details",
+        }
+    }
+}
 
 pub fn alert(title: &str, body: &str, window: &Window) {
     let alert = AlertDialog::builder()
@@ -28,6 +56,65 @@ pub fn alert(title: &str, body: &str, window: &Window) {
     );
 }
 
+/// Explains `err` with the recoveries that make sense for it, `None` when the user just closes
+/// the dialog. Nothing is shown for [`ProviderError::Cancelled`].
+pub async fn error(
+    title: &str,
+    err: &ProviderError,
+    can_retry: bool,
+    window: &Window,
+) -> Option<Recovery> {
+    let (detail, mut recoveries) = match err {
+        ProviderError::Unavailable(program) => (
+            format!("{program} could not be started."),
+            vec![Recovery::FindProviders],
+        ),
+        ProviderError::AuthFailed => (
+            "The password was not accepted, it will be asked again.".to_string(),
+            vec![Recovery::Retry],
+        ),
+        ProviderError::Network(message) => (message.clone(), vec![Recovery::Retry]),
+        ProviderError::Parse { line, reason } => (
+            format!("The output could not be read ({reason}):\n{line}"),
+            vec![Recovery::CopyDetails],
+        ),
+        ProviderError::CommandFailed { .. } => (
+            err.to_string(),
+            vec![Recovery::CopyDetails, Recovery::Retry],
+        ),
+        ProviderError::LockedDatabase(message) => (
+            format!("Another package manager is using the database.\n{message}"),
+            vec![Recovery::Retry],
+        ),
+        ProviderError::PackageNotFound(message) => (message.clone(), vec![Recovery::Reload]),
+        ProviderError::Cancelled => return None,
+        ProviderError::Other(err) => (format!("{err:?}"), vec![Recovery::CopyDetails]),
+    };
+    if !can_retry {
+        recoveries.retain(|recovery| *recovery != Recovery::Retry);
+    }
+    let detail = match err.hint() {
+        Some(hint) => format!("{detail}\n\n{hint}"),
+        None => detail,
+    };
+
+    let buttons: Vec<&str> = std::iter::once("Close")
+        .chain(recoveries.iter().map(Recovery::label))
+        .collect();
+    let alert = AlertDialog::builder()
+        .message(title)
+        .detail(detail)
+        .buttons(buttons.as_slice())
+        .cancel_button(0)
+        .default_button(recoveries.len().min(1) as i32)
+        .modal(true)
+        .build();
+
+    let choice = alert.choose_future(Some(window)).await.ok()?;
+    recoveries.get((choice as usize).checked_sub(1)?).copied()
+}
+
+/// Asks for the sudo password until it is accepted, up to [`PASSWORD_ATTEMPTS`] times.
 pub async fn ask_password(window: &Window) -> Option<SecVec<u8>> {
     let (sender, receiver) = async_channel::unbounded();
 
@@ -42,9 +129,12 @@ pub async fn ask_password(window: &Window) -> Option<SecVec<u8>> {
 
     let text = gtk::Label::builder().label("Password").build();
     let password = gtk::Entry::builder().text("").visibility(false).build();
+    let error = gtk::Label::builder().visible(false).wrap(true).build();
+    error.add_css_class("error");
     let button = gtk::Button::builder().label("Ok").build();
     child.append(&text);
     child.append(&password);
+    child.append(&error);
     child.append(&button);
 
     let dialog = gtk::Window::builder()
@@ -70,30 +160,144 @@ pub async fn ask_password(window: &Window) -> Option<SecVec<u8>> {
 
     dialog.set_visible(true);
 
-    let response = receiver.recv().await;
-    if !response.unwrap_or(false) {
-        return None;
+    for attempt in 1..=PASSWORD_ATTEMPTS {
+        if !receiver.recv().await.unwrap_or(false) {
+            return None;
+        }
+
+        let pass = SecStr::from(password.text().to_string());
+        password.set_sensitive(false);
+        button.set_sensitive(false);
+        let verified = verify_password(pass.clone()).await;
+        password.set_sensitive(true);
+        button.set_sensitive(true);
+
+        let message = match verified {
+            Ok(()) => {
+                dialog.close();
+                return Some(pass);
+            }
+            Err(err) if attempt == PASSWORD_ATTEMPTS => {
+                dialog.close();
+                alert(
+                    "Wrong Password",
+                    &format!("Please provide the correct password.\n{err:?}"),
+                    window,
+                );
+                return None;
+            }
+            Err(_) => format!(
+                "Wrong password, {} attempts left.",
+                PASSWORD_ATTEMPTS - attempt
+            ),
+        };
+        error.set_label(&message);
+        error.set_visible(true);
+        password.set_text("");
+        password.grab_focus();
     }
+    None
+}
 
-    let pass = password.text().to_string();
+/// Checks the password with sudo on a worker thread.
+async fn verify_password(password: SecVec<u8>) -> anyhow::Result<()> {
+    let (sender, receiver) = async_channel::bounded(1);
+    std::thread::spawn(move || {
+        let _ = sender.send_blocking(Privilege::current().verify(password));
+    });
+    receiver.recv().await?
+}
 
-    let check_password = command::run(&format!(
-        "echo \"{}\" | su -c \"true\" root 2>/dev/null",
-        password.text()
-    ));
+/// Asks the user to answer a prompt of a running command, `None` when the dialog is dismissed.
+pub async fn ask_prompt(window: &Window, context: &str, prompt: &str) -> Option<String> {
+    let (sender, receiver) = async_channel::unbounded();
 
-    dialog.close();
-    let res = match check_password {
-        Ok(_) => SecStr::from(pass),
-        Err(err) => {
-            alert(
-                "Wrong Password",
-                &format!("Please provide the currect password.\n{err:?}"),
-                window,
-            );
-            return None;
+    let child = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(5)
+        .margin_top(10)
+        .margin_bottom(10)
+        .margin_end(10)
+        .margin_start(10)
+        .build();
+
+    let output = gtk::Label::builder()
+        .label(context)
+        .halign(gtk::Align::Start)
+        .wrap(true)
+        .selectable(true)
+        .build();
+    output.add_css_class("monospace");
+    let text = gtk::Label::builder()
+        .label(prompt)
+        .halign(gtk::Align::Start)
+        .wrap(true)
+        .build();
+    text.add_css_class("heading");
+    child.append(&output);
+    child.append(&text);
+
+    let buttons = gtk::Box::builder()
+        .orientation(gtk::Orientation::Horizontal)
+        .spacing(5)
+        .halign(gtk::Align::End)
+        .build();
+
+    let lower = prompt.to_lowercase();
+    let entry = if lower.contains("[y/n]") {
+        for (label, answer) in [("Yes", "y"), ("No", "n")] {
+            let button = gtk::Button::builder().label(label).build();
+            let btn_sender = sender.clone();
+            button.connect_clicked(move |_| {
+                let _ = btn_sender.send_blocking(Some(answer.to_string()));
+            });
+            buttons.append(&button);
         }
+        None
+    } else {
+        // Asked again when the given password was rejected
+        let entry = gtk::Entry::builder()
+            .text("")
+            .visibility(!lower.contains("password"))
+            .build();
+        let button = gtk::Button::builder().label("Ok").build();
+        child.append(&entry);
+        buttons.append(&button);
+
+        let btn_sender = sender.clone();
+        button.connect_clicked(clone!(
+            #[weak]
+            entry,
+            move |_| {
+                let _ = btn_sender.send_blocking(Some(entry.text().to_string()));
+            }
+        ));
+        let entry_sender = sender.clone();
+        entry.connect_activate(move |entry| {
+            let _ = entry_sender.send_blocking(Some(entry.text().to_string()));
+        });
+        Some(entry)
     };
-    let _ = command::run("sudo -k");
-    Some(res)
+    child.append(&buttons);
+
+    let dialog = gtk::Window::builder()
+        .transient_for(window)
+        .title("Command Input")
+        .child(&child)
+        .modal(true)
+        .build();
+
+    dialog.connect_close_request(move |_| {
+        let _ = sender.send_blocking(None);
+        gtk::glib::Propagation::Proceed
+    });
+
+    dialog.set_visible(true);
+    if let Some(entry) = entry {
+        entry.grab_focus();
+    }
+
+    let response = receiver.recv().await.unwrap_or(None);
+    dialog.close();
+    response
 }
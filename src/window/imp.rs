@@ -1,12 +1,26 @@
 use crate::{
-    backend::{package_object::PackageObject, provider::ProviderKind},
+    backend::{
+        ansi,
+        command::Canceller,
+        details::PackageDetails,
+        history::Transaction,
+        package_object::PackageObject,
+        provider::{Change, ProviderKind},
+        query::Query,
+        search::SearchIndex,
+    },
     grid_check, grid_text, messagebox,
 };
 use adw::subclass::prelude::*;
 use glib::subclass::InitializingObject;
 use gtk::{glib, prelude::*, CompositeTemplate};
 use secstr::SecVec;
-use std::cell::RefCell;
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::SystemTime,
+};
 #[derive(CompositeTemplate, Default)]
 #[template(resource = "/org/caioxcezar/packagemanager/window.ui")]
 pub struct Window {
@@ -21,16 +35,72 @@ pub struct Window {
     #[template_child]
     pub update_all: TemplateChild<gtk::Button>,
     #[template_child]
+    pub refreshed_label: TemplateChild<gtk::Label>,
+    #[template_child]
+    pub search_error: TemplateChild<gtk::Label>,
+    #[template_child]
+    pub pending_bar: TemplateChild<gtk::Box>,
+    #[template_child]
+    pub pending_label: TemplateChild<gtk::Label>,
+    #[template_child]
+    pub pending_clear: TemplateChild<gtk::Button>,
+    #[template_child]
+    pub pending_apply: TemplateChild<gtk::Button>,
+    #[template_child]
     pub action: TemplateChild<gtk::Button>,
     #[template_child]
     pub update: TemplateChild<gtk::Button>,
     #[template_child]
+    pub show_updates: TemplateChild<gtk::Button>,
+    #[template_child]
+    pub updates_label: TemplateChild<gtk::Label>,
+    #[template_child]
+    pub updates_list: TemplateChild<gtk::ListBox>,
+    #[template_child]
+    pub updates_back: TemplateChild<gtk::Button>,
+    #[template_child]
+    pub updates_select_all: TemplateChild<gtk::Button>,
+    #[template_child]
+    pub updates_apply: TemplateChild<gtk::Button>,
+    #[template_child]
+    pub history_label: TemplateChild<gtk::Label>,
+    #[template_child]
+    pub history_list: TemplateChild<gtk::ListBox>,
+    #[template_child]
+    pub history_transcript: TemplateChild<gtk::TextView>,
+    #[template_child]
+    pub history_back: TemplateChild<gtk::Button>,
+    #[template_child]
+    pub history_undo: TemplateChild<gtk::Button>,
+    #[template_child]
+    pub details_pane: TemplateChild<gtk::Box>,
+    #[template_child]
+    pub details_stack: TemplateChild<gtk::Stack>,
+    #[template_child]
+    pub details_box: TemplateChild<gtk::Box>,
+    #[template_child]
+    pub details_spinner: TemplateChild<adw::Spinner>,
+    #[template_child]
     pub text_box: TemplateChild<gtk::TextView>,
     #[template_child]
     pub text_command: TemplateChild<gtk::TextView>,
     #[template_child]
     pub text_command_buffer: TemplateChild<gtk::TextBuffer>,
     #[template_child]
+    pub command_progress: TemplateChild<gtk::ProgressBar>,
+    #[template_child]
+    pub command_search: TemplateChild<gtk::SearchEntry>,
+    #[template_child]
+    pub command_search_previous: TemplateChild<gtk::Button>,
+    #[template_child]
+    pub command_search_next: TemplateChild<gtk::Button>,
+    #[template_child]
+    pub command_copy: TemplateChild<gtk::Button>,
+    #[template_child]
+    pub command_save: TemplateChild<gtk::Button>,
+    #[template_child]
+    pub cancel_button: TemplateChild<gtk::Button>,
+    #[template_child]
     pub info_bar: TemplateChild<gtk::Overlay>,
     #[template_child]
     pub info_bar_label: TemplateChild<gtk::Label>,
@@ -42,19 +112,66 @@ pub struct Window {
     #[template_child]
     pub column_view: TemplateChild<gtk::ColumnView>,
     #[template_child]
-    pub single_selection: TemplateChild<gtk::SingleSelection>,
+    pub selection: TemplateChild<gtk::MultiSelection>,
     #[template_child]
     pub column_installed: TemplateChild<gtk::ColumnViewColumn>,
     #[template_child]
     pub column_name: TemplateChild<gtk::ColumnViewColumn>,
     #[template_child]
-    pub column_version: TemplateChild<gtk::ColumnViewColumn>,
+    pub column_provider: TemplateChild<gtk::ColumnViewColumn>,
+    #[template_child]
+    pub column_status: TemplateChild<gtk::ColumnViewColumn>,
+    #[template_child]
+    pub column_installed_version: TemplateChild<gtk::ColumnViewColumn>,
+    #[template_child]
+    pub column_available_version: TemplateChild<gtk::ColumnViewColumn>,
     #[template_child]
     pub column_repository: TemplateChild<gtk::ColumnViewColumn>,
+    #[template_child]
+    pub column_description: TemplateChild<gtk::ColumnViewColumn>,
+    #[template_child]
+    pub column_size: TemplateChild<gtk::ColumnViewColumn>,
+    #[template_child]
+    pub column_license: TemplateChild<gtk::ColumnViewColumn>,
+    #[template_child]
+    pub column_url: TemplateChild<gtk::ColumnViewColumn>,
+    #[template_child]
+    pub column_install_date: TemplateChild<gtk::ColumnViewColumn>,
+    #[template_child]
+    pub column_popularity: TemplateChild<gtk::ColumnViewColumn>,
 
     pub filter_list: gtk::FilterListModel,
     pub providers: RefCell<Vec<ProviderKind>>,
+    /// Providers listed from the catalog until they have loaded.
+    pub cached: RefCell<HashSet<String>>,
+    pub refreshed: RefCell<HashMap<String, SystemTime>>,
+    pub indexes: RefCell<HashMap<String, Arc<SearchIndex>>>,
+    /// The providers in the package list.
+    pub listed: RefCell<Vec<super::Listed>>,
+    pub query: RefCell<Query>,
+    /// Tells the results of an outdated search apart.
+    pub search_generation: Cell<u64>,
+    /// Provider and name of the package to select once the search for it is done.
+    pub pending_selection: RefCell<Option<(String, String)>>,
+    /// Tells the details of an outdated selection apart.
+    pub details_generation: Cell<u64>,
+    /// Details recently shown for each provider, the latest last.
+    pub details_cache: RefCell<HashMap<String, Vec<(String, PackageDetails)>>>,
+    /// Hands the packages to show to the thread fetching their details.
+    pub details_requests: OnceCell<async_channel::Sender<super::DetailsRequest>>,
+    /// Transactions listed in the history page, in the order of its rows.
+    pub transactions: RefCell<Vec<Transaction>>,
+    /// Whether recording the running commands in the history failed, told once they end.
+    pub history_failed: Cell<bool>,
+    /// Transactions waiting for their provider to reload to record the versions they left.
+    pub awaiting_versions: RefCell<Vec<(String, i64)>>,
+    pub pending_updates: RefCell<Vec<(gtk::CheckButton, String)>>,
+    pub pending_changes: RefCell<Vec<(String, Change)>>,
+    pub running: RefCell<Option<Canceller>>,
+    pub ansi_style: RefCell<ansi::Style>,
+    pub progress_mark: RefCell<Option<gtk::TextMark>>,
     pub password: RefCell<Option<SecVec<u8>>>,
+    pub password_expiry: RefCell<Option<glib::SourceId>>,
 }
 
 #[glib::object_subclass]
@@ -91,7 +208,10 @@ impl ObjectImpl for Window {
                 let obj = window.obj();
 
                 obj.setup_sorter();
+                obj.setup_dropdown();
+                obj.setup_actions();
                 obj.setup_signals();
+                obj.setup_details_worker();
                 obj.setup_data();
             }
         ));
@@ -132,12 +252,50 @@ impl Window {
         signal_text_bind_handler(item, entry.name());
     }
     #[template_callback]
-    fn signal_version_bind_handler(_factory: gtk::SignalListItemFactory, item: gtk::ListItem) {
+    fn signal_provider_bind_handler(_factory: gtk::SignalListItemFactory, item: gtk::ListItem) {
+        let entry = match item.item().and_downcast::<PackageObject>() {
+            Some(v) => v,
+            None => return,
+        };
+        signal_text_bind_handler(item, entry.provider());
+    }
+    #[template_callback]
+    fn signal_status_bind_handler(_factory: gtk::SignalListItemFactory, item: gtk::ListItem) {
+        let entry = match item.item().and_downcast::<PackageObject>() {
+            Some(v) => v,
+            None => return,
+        };
+        let child = match item.child().and_downcast::<grid_text::GridText>() {
+            Some(v) => v,
+            None => return,
+        };
+        let ent = grid_text::Entry {
+            name: entry.status(),
+        };
+        child.set_entry(&ent);
+        child.set_status(entry.status_kind());
+    }
+    #[template_callback]
+    fn signal_installed_version_bind_handler(
+        _factory: gtk::SignalListItemFactory,
+        item: gtk::ListItem,
+    ) {
         let entry = match item.item().and_downcast::<PackageObject>() {
             Some(v) => v,
             None => return,
         };
-        signal_text_bind_handler(item, entry.version());
+        signal_text_bind_handler(item, entry.installedVersion().unwrap_or_default());
+    }
+    #[template_callback]
+    fn signal_available_version_bind_handler(
+        _factory: gtk::SignalListItemFactory,
+        item: gtk::ListItem,
+    ) {
+        let entry = match item.item().and_downcast::<PackageObject>() {
+            Some(v) => v,
+            None => return,
+        };
+        signal_text_bind_handler(item, entry.availableVersion().unwrap_or_default());
     }
     #[template_callback]
     fn signal_repository_bind_handler(_factory: gtk::SignalListItemFactory, item: gtk::ListItem) {
@@ -148,6 +306,68 @@ impl Window {
         signal_text_bind_handler(item, entry.repository());
     }
     #[template_callback]
+    fn signal_description_bind_handler(_factory: gtk::SignalListItemFactory, item: gtk::ListItem) {
+        let entry = match item.item().and_downcast::<PackageObject>() {
+            Some(v) => v,
+            None => return,
+        };
+        signal_text_bind_handler(item, entry.description().unwrap_or_default());
+    }
+    #[template_callback]
+    fn signal_size_bind_handler(_factory: gtk::SignalListItemFactory, item: gtk::ListItem) {
+        let entry = match item.item().and_downcast::<PackageObject>() {
+            Some(v) => v,
+            None => return,
+        };
+        let size = match entry.size() {
+            0 => String::new(),
+            size => glib::format_size(size).to_string(),
+        };
+        signal_text_bind_handler(item, size);
+    }
+    #[template_callback]
+    fn signal_license_bind_handler(_factory: gtk::SignalListItemFactory, item: gtk::ListItem) {
+        let entry = match item.item().and_downcast::<PackageObject>() {
+            Some(v) => v,
+            None => return,
+        };
+        signal_text_bind_handler(item, entry.license());
+    }
+    #[template_callback]
+    fn signal_url_bind_handler(_factory: gtk::SignalListItemFactory, item: gtk::ListItem) {
+        let entry = match item.item().and_downcast::<PackageObject>() {
+            Some(v) => v,
+            None => return,
+        };
+        signal_text_bind_handler(item, entry.url().unwrap_or_default());
+    }
+    #[template_callback]
+    fn signal_install_date_bind_handler(_factory: gtk::SignalListItemFactory, item: gtk::ListItem) {
+        let entry = match item.item().and_downcast::<PackageObject>() {
+            Some(v) => v,
+            None => return,
+        };
+        let date = match entry.installDate() {
+            0 => None,
+            date => glib::DateTime::from_unix_local(date)
+                .and_then(|date| date.format("%x"))
+                .ok(),
+        };
+        signal_text_bind_handler(item, date.map(String::from).unwrap_or_default());
+    }
+    #[template_callback]
+    fn signal_popularity_bind_handler(_factory: gtk::SignalListItemFactory, item: gtk::ListItem) {
+        let entry = match item.item().and_downcast::<PackageObject>() {
+            Some(v) => v,
+            None => return,
+        };
+        let popularity = match entry.popularity() {
+            0.0 => String::new(),
+            popularity => format!("{popularity:.2}"),
+        };
+        signal_text_bind_handler(item, popularity);
+    }
+    #[template_callback]
     fn handle_focused(&self) {
         self.dropdown_provider.set_selected(0);
     }
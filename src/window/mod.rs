@@ -5,20 +5,69 @@ use async_channel::unbounded;
 use gtk::{
     gio,
     glib::{self, clone, GString, Object},
+    pango,
+};
+use std::{
+    cell::Ref,
+    collections::HashMap,
+    sync::Arc,
+    thread::spawn,
+    time::{Duration, SystemTime},
 };
-use std::{cell::Ref, thread::spawn};
 
 use anyhow::{anyhow, Context, Result};
-use rust_fuzzy_search::fuzzy_compare;
 use secstr::{SecStr, SecVec};
 
 use crate::{
     application,
-    backend::{command::CommandStream, settings},
-    backend::{package_object::PackageObject, provider::ProviderKind},
-    messagebox,
+    backend::{
+        ansi,
+        catalog::{Catalog, Snapshot},
+        command::{Canceller, CommandStream, Line, Outcome, Responder},
+        details::{self, PackageDetails},
+        error::ProviderError,
+        history::{Action, Entry, History, Status, Transaction},
+        package::PackageStatus,
+        settings,
+    },
+    backend::{
+        package_object::PackageObject,
+        privilege::Privilege,
+        provider::{Change, Operation, ProviderKind},
+        provider_object::{ProviderObject, ProviderStatus},
+        query::Query,
+        search::{self, SearchIndex},
+    },
+    messagebox::{self, Recovery},
 };
 
+/// Lines of output shown above a prompt so the user knows what is being asked.
+const PROMPT_CONTEXT_LINES: usize = 15;
+/// Entry of the dropdown listing the packages of every provider together.
+const ALL_PROVIDERS: &str = "All providers";
+/// Links of the details pane to another package, followed by its name.
+const PACKAGE_SCHEME: &str = "package:";
+/// Details kept for each provider, so going back to a package doesn't run the provider again.
+const DETAILS_CACHE_SIZE: usize = 32;
+/// Transactions listed in the history page.
+const HISTORY_LIMIT: usize = 200;
+/// Packages named in the summary of a transaction before the count of the others.
+const SUMMARY_PACKAGES: usize = 3;
+
+/// A package whose details are to be fetched, dropped when a newer one is asked for first.
+pub struct DetailsRequest {
+    generation: u64,
+    provider: ProviderKind,
+    package: String,
+}
+
+/// The packages of a provider in the list, with the index they are searched with.
+pub struct Listed {
+    provider: String,
+    index: Arc<SearchIndex>,
+    objects: Vec<PackageObject>,
+}
+
 glib::wrapper! {
     pub struct Window(ObjectSubclass<imp::Window>)
         @extends adw::ApplicationWindow, gtk::ApplicationWindow, gtk::Window, gtk::Widget,
@@ -58,59 +107,190 @@ impl Window {
         let sorter = sorter_string_package("name");
         obj.column_name.set_sorter(Some(&sorter));
 
-        let sorter = sorter_string_package("version");
-        obj.column_version.set_sorter(Some(&sorter));
+        let sorter = sorter_string_package("provider");
+        obj.column_provider.set_sorter(Some(&sorter));
+
+        let sorter = sorter_string_package("status");
+        obj.column_status.set_sorter(Some(&sorter));
+
+        let sorter = sorter_string_package("installedVersion");
+        obj.column_installed_version.set_sorter(Some(&sorter));
+
+        let sorter = sorter_string_package("availableVersion");
+        obj.column_available_version.set_sorter(Some(&sorter));
 
         let sorter = sorter_string_package("repository");
         obj.column_repository.set_sorter(Some(&sorter));
+
+        let sorter = sorter_string_package("description");
+        obj.column_description.set_sorter(Some(&sorter));
+
+        let sorter = sorter_numeric_package("size");
+        obj.column_size.set_sorter(Some(&sorter));
+
+        let sorter = sorter_string_package("license");
+        obj.column_license.set_sorter(Some(&sorter));
+
+        let sorter = sorter_string_package("url");
+        obj.column_url.set_sorter(Some(&sorter));
+
+        let sorter = sorter_numeric_package("installDate");
+        obj.column_install_date.set_sorter(Some(&sorter));
+
+        let sorter = sorter_numeric_package("popularity");
+        obj.column_popularity.set_sorter(Some(&sorter));
+    }
+
+    fn setup_actions(&self) {
+        let forget_password = gio::SimpleAction::new("forget-password", None);
+        forget_password.set_enabled(false);
+        forget_password.connect_activate(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| window.forget_password()
+        ));
+        self.add_action(&forget_password);
+
+        let show_history = gio::SimpleAction::new("show-history", None);
+        show_history.connect_activate(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_, _| {
+                if let Err(err) = window.handle_show_history() {
+                    window.show_error("Failed to show the history", err, None);
+                }
+            }
+        ));
+        self.add_action(&show_history);
+
+        // One toggle per optional column, remembered in the settings
+        let shown = settings::get()
+            .map(|settings| settings.columns)
+            .unwrap_or_default();
+        for (name, column) in self.optional_columns() {
+            let visible = shown.iter().any(|shown| shown == name);
+            column.set_visible(visible);
+            let action = gio::SimpleAction::new_stateful(
+                &format!("column-{}", name.replace('_', "-")),
+                None,
+                &visible.to_variant(),
+            );
+            action.connect_change_state(move |action, value| {
+                let Some(visible) = value.and_then(|value| value.get::<bool>()) else {
+                    return;
+                };
+                column.set_visible(visible);
+                action.set_state(&visible.to_variant());
+                if let Ok(mut settings) = settings::get() {
+                    settings.columns.retain(|shown| shown != name);
+                    if visible {
+                        settings.columns.push(name.to_string());
+                    }
+                    if let Err(err) = settings.update_json() {
+                        eprintln!("Failed to save the columns: {err:?}");
+                    }
+                }
+            });
+            self.add_action(&action);
+        }
+    }
+
+    /// The columns hidden unless chosen in the menu, by the name they are saved under.
+    fn optional_columns(&self) -> [(&'static str, gtk::ColumnViewColumn); 6] {
+        let obj = self.imp();
+        [
+            ("description", obj.column_description.get()),
+            ("size", obj.column_size.get()),
+            ("license", obj.column_license.get()),
+            ("url", obj.column_url.get()),
+            ("install_date", obj.column_install_date.get()),
+            ("popularity", obj.column_popularity.get()),
+        ]
     }
 
     fn setup_signals(&self) {
         let obj = self.imp();
 
+        glib::timeout_add_seconds_local(
+            60,
+            clone!(
+                #[weak(rename_to = window)]
+                self,
+                #[upgrade_or]
+                glib::ControlFlow::Break,
+                move || {
+                    window.refresh_refreshed_label();
+                    glib::ControlFlow::Continue
+                }
+            ),
+        );
+
         obj.update_all.connect_clicked(clone!(
             #[weak(rename_to = window)]
             self,
             move |_| {
-                glib::spawn_future_local(async move { window.handle_update_all().await });
+                glib::spawn_future_local(async move {
+                    if let Err(err) = window.handle_update_all().await {
+                        window.show_error(
+                            "Failed to update all",
+                            err,
+                            Some(|window| window.imp().update_all.emit_clicked()),
+                        );
+                    }
+                });
             }
         ));
 
         obj.dropdown_provider.connect_selected_item_notify(clone!(
             #[weak(rename_to = window)]
             self,
-            move |_| {
-                if let Err(err) = window.handle_dropdown_changed() {
-                    messagebox::alert(
-                        "Failed to change the dropdown",
-                        &format!("{err:?}"),
-                        &window,
-                    );
-                };
-            }
+            move |_| window.handle_dropdown_changed()
         ));
 
-        obj.single_selection.connect_selection_changed(clone!(
+        obj.selection.connect_selection_changed(clone!(
             #[weak(rename_to = window)]
             self,
-            move |grid, _position, _n_items| {
-                if let Err(err) = window.handle_selection_changed(grid) {
-                    messagebox::alert(
-                        "Failed to change the dropdown",
-                        &format!("{err:?}"),
-                        &window,
+            move |_selection, _position, _n_items| {
+                if let Err(err) = window.handle_selection_changed() {
+                    window.show_error(
+                        "Failed to show the package",
+                        err,
+                        Some(|window| window.imp().selection.selection_changed(0, 0)),
                     );
                 };
             }
         ));
 
         obj.action.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| {
+                if let Err(err) = window.handle_action() {
+                    window.show_error("Failed to mark packages", err, None);
+                }
+            }
+        ));
+
+        obj.pending_clear.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| {
+                window.imp().pending_changes.borrow_mut().clear();
+                window.refresh_pending_changes();
+            }
+        ));
+
+        obj.pending_apply.connect_clicked(clone!(
             #[weak(rename_to = window)]
             self,
             move |_button| {
                 glib::spawn_future_local(async move {
-                    if let Err(err) = window.handle_action().await {
-                        messagebox::alert("Failed to execute action", &format!("{err:?}"), &window);
+                    if let Err(err) = window.handle_apply_changes().await {
+                        window.show_error(
+                            "Failed to apply changes",
+                            err,
+                            Some(|window| window.imp().pending_apply.emit_clicked()),
+                        );
                     }
                 });
             }
@@ -122,18 +302,158 @@ impl Window {
             move |_button| {
                 glib::spawn_future_local(async move {
                     if let Err(err) = window.handle_update().await {
-                        messagebox::alert("Failed to update", &format!("{err:?}"), &window);
+                        window.show_error(
+                            "Failed to update",
+                            err,
+                            Some(|window| window.imp().update.emit_clicked()),
+                        );
+                    }
+                });
+            }
+        ));
+
+        obj.show_updates.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| {
+                glib::spawn_future_local(async move {
+                    if let Err(err) = window.handle_show_updates().await {
+                        window.show_error(
+                            "Failed to list pending updates",
+                            err,
+                            Some(|window| window.imp().show_updates.emit_clicked()),
+                        );
+                    }
+                });
+            }
+        ));
+
+        obj.updates_back.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| {
+                if let Err(err) = window.goto_main() {
+                    window.show_error("Failed to change page", err, None);
+                }
+            }
+        ));
+
+        obj.updates_select_all.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| {
+                for (check, _) in window.imp().pending_updates.borrow().iter() {
+                    check.set_active(true);
+                }
+            }
+        ));
+
+        obj.updates_apply.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| {
+                glib::spawn_future_local(async move {
+                    if let Err(err) = window.handle_apply_updates().await {
+                        window.show_error(
+                            "Failed to upgrade",
+                            err,
+                            Some(|window| window.imp().updates_apply.emit_clicked()),
+                        );
+                    }
+                });
+            }
+        ));
+
+        obj.history_back.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| {
+                if let Err(err) = window.goto_main() {
+                    window.show_error("Failed to change page", err, None);
+                }
+            }
+        ));
+
+        obj.history_list.connect_row_selected(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_list, row| window.handle_history_selected(row)
+        ));
+
+        obj.history_undo.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| {
+                glib::spawn_future_local(async move {
+                    if let Err(err) = window.handle_history_undo().await {
+                        window.show_error(
+                            "Failed to undo",
+                            err,
+                            Some(|window| window.imp().history_undo.emit_clicked()),
+                        );
+                    }
+                });
+            }
+        ));
+
+        obj.command_search.connect_search_changed(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_entry| {
+                let buffer = &window.imp().text_command_buffer;
+                buffer.place_cursor(&buffer.start_iter());
+                window.handle_command_search(true);
+            }
+        ));
+
+        obj.command_search.connect_activate(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_entry| window.handle_command_search(true)
+        ));
+
+        obj.command_search_next.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| window.handle_command_search(true)
+        ));
+
+        obj.command_search_previous.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| window.handle_command_search(false)
+        ));
+
+        obj.command_copy.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| window.handle_command_copy()
+        ));
+
+        obj.command_save.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| {
+                glib::spawn_future_local(async move {
+                    if let Err(err) = window.handle_command_save().await {
+                        window.show_error("Failed to save transcript", err, None);
                     }
                 });
             }
         ));
 
+        obj.cancel_button.connect_clicked(clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_button| window.handle_cancel()
+        ));
+
         obj.info_bar_button.connect_clicked(clone!(
             #[weak(rename_to = window)]
             self,
             move |button| {
                 if let Err(err) = window.handle_info_bar_clicked(button) {
-                    messagebox::alert("Failed to change page", &format!("{err:?}"), &window);
+                    window.show_error("Failed to change page", err, None);
                 }
             }
         ));
@@ -143,83 +463,407 @@ impl Window {
             self,
             move |entry| {
                 if let Err(err) = window.handle_search(entry) {
-                    messagebox::alert("Error while searching", &format!("{err:?}"), &window);
+                    window.show_error("Error while searching", err, None);
                 }
             }
         ));
     }
 
+    /// Shows the name of each provider with a spinner while it loads and a warning when it failed.
+    fn setup_dropdown(&self) {
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(|_, item| {
+            let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+                return;
+            };
+            let label = gtk::Label::builder().xalign(0.0).hexpand(true).build();
+            let spinner = adw::Spinner::new();
+            let failed = gtk::Image::from_icon_name("dialog-warning-symbolic");
+            failed.add_css_class("warning");
+            let row = gtk::Box::builder()
+                .orientation(gtk::Orientation::Horizontal)
+                .spacing(6)
+                .build();
+            row.append(&label);
+            row.append(&spinner);
+            row.append(&failed);
+
+            let provider = item.property_expression("item");
+            provider.chain_property::<ProviderObject>("name").bind(
+                &label,
+                "label",
+                gtk::Widget::NONE,
+            );
+            provider.chain_property::<ProviderObject>("loading").bind(
+                &spinner,
+                "visible",
+                gtk::Widget::NONE,
+            );
+            provider.chain_property::<ProviderObject>("failed").bind(
+                &failed,
+                "visible",
+                gtk::Widget::NONE,
+            );
+            provider.chain_property::<ProviderObject>("error").bind(
+                &failed,
+                "tooltip-text",
+                gtk::Widget::NONE,
+            );
+            item.set_child(Some(&row));
+        });
+
+        let dropdown = &self.imp().dropdown_provider;
+        dropdown.set_factory(Some(&factory));
+        dropdown.set_expression(Some(gtk::PropertyExpression::new(
+            ProviderObject::static_type(),
+            gtk::Expression::NONE,
+            "name",
+        )));
+    }
+
+    /// Looks for the providers on a worker thread, then loads each of them on its own so a slow
+    /// or broken one doesn't hold back the others.
     fn setup_data(&self) {
         let obj = self.imp();
+        obj.update_all.set_sensitive(false);
+        obj.update.set_sensitive(false);
+        obj.show_updates.set_sensitive(false);
 
-        let providers = ProviderKind::available_providers();
+        glib::spawn_future_local(clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                let (sender, receiver) = async_channel::bounded(1);
+                spawn(move || {
+                    let providers = ProviderKind::available_providers();
+                    let snapshots: Vec<(String, SystemTime, Arc<SearchIndex>)> =
+                        cached_snapshots(&providers)
+                            .into_iter()
+                            .map(|(name, snapshot)| {
+                                let index = Arc::new(SearchIndex::new(snapshot.packages));
+                                (name, snapshot.refreshed, index)
+                            })
+                            .collect();
+                    let _ = sender.send_blocking((providers, snapshots));
+                });
+                let (providers, snapshots) = receiver.recv().await.unwrap_or_default();
 
-        let model = providers
-            .iter()
-            .map(|provider| provider.name())
-            .collect::<gtk::StringList>();
+                let obj = window.imp();
+                obj.cached
+                    .replace(snapshots.iter().map(|(name, _, _)| name.clone()).collect());
+                obj.refreshed.replace(
+                    snapshots
+                        .iter()
+                        .map(|(name, refreshed, _)| (name.clone(), *refreshed))
+                        .collect(),
+                );
+                obj.indexes.replace(
+                    snapshots
+                        .into_iter()
+                        .map(|(name, _, index)| (name, index))
+                        .collect(),
+                );
+                let items: Vec<ProviderObject> = providers
+                    .iter()
+                    .map(|provider| {
+                        let item = ProviderObject::new(&provider.name());
+                        item.set_status(ProviderStatus::Loading);
+                        item
+                    })
+                    .collect();
+                let found = !providers.is_empty();
+                obj.update_all.set_sensitive(found);
+                obj.update.set_sensitive(found);
+                obj.show_updates.set_sensitive(found);
+                obj.providers.replace(providers.clone());
+                let all = ProviderObject::new(ALL_PROVIDERS);
+                all.set_status(ProviderStatus::Loading);
+                let model = gio::ListStore::from_iter(items.clone());
+                if providers.len() > 1 {
+                    model.append(&all);
+                }
+                obj.dropdown_provider.set_model(Some(&model));
+                if !obj.cached.borrow().is_empty() {
+                    window.show_packages();
+                }
+
+                for (provider, item) in providers.into_iter().zip(items) {
+                    window.spawn_load(provider, item);
+                }
+            }
+        ));
+    }
 
-        if providers.is_empty() {
-            obj.update_all.set_sensitive(false);
-            obj.update.set_sensitive(false);
+    /// Loads the packages of the provider named `name` again, unless they are already loading.
+    fn load_provider(&self, name: &str) {
+        let obj = self.imp();
+        if name == ALL_PROVIDERS {
+            let names: Vec<String> = obj.providers.borrow().iter().map(|p| p.name()).collect();
+            for name in names {
+                self.load_provider(&name);
+            }
+            return;
+        }
+        let Some(item) = self
+            .provider_items()
+            .into_iter()
+            .find(|item| item.name() == name)
+        else {
+            return;
+        };
+        if item.loading() {
+            return;
         }
-        obj.providers.replace(providers);
-        obj.dropdown_provider.set_model(Some(&model));
+        let Some(provider) = obj
+            .providers
+            .borrow()
+            .iter()
+            .find(|provider| provider.name() == name)
+            .cloned()
+        else {
+            return;
+        };
+        item.set_status(ProviderStatus::Loading);
+        self.refresh_all_item();
+        self.refresh_refreshed_label();
+        self.spawn_load(provider, item);
+    }
+
+    /// The entries of the dropdown, "All providers" included.
+    fn provider_items(&self) -> Vec<ProviderObject> {
+        self.imp()
+            .dropdown_provider
+            .model()
+            .map(|model| model.iter::<ProviderObject>().flatten().collect())
+            .unwrap_or_default()
+    }
+
+    /// "All providers" is loading while any of the providers is.
+    fn refresh_all_item(&self) {
+        let items = self.provider_items();
+        let Some(all) = items.iter().find(|item| item.name() == ALL_PROVIDERS) else {
+            return;
+        };
+        let status = if items.iter().any(|item| item.loading()) {
+            ProviderStatus::Loading
+        } else {
+            ProviderStatus::Ready
+        };
+        all.set_status(status);
+    }
+
+    fn spawn_load(&self, mut provider: ProviderKind, item: ProviderObject) {
+        // The transactions of the provider that finished before it started loading
+        let mut awaiting = Vec::new();
+        self.imp()
+            .awaiting_versions
+            .borrow_mut()
+            .retain(|(name, id)| {
+                let matches = *name == provider.name();
+                if matches {
+                    awaiting.push(*id);
+                }
+                !matches
+            });
+        let unrecorded = awaiting.clone();
+
+        let (sender, receiver) = async_channel::bounded(1);
+        spawn(move || {
+            let result = provider.update_packages().map(|()| {
+                let refreshed = Catalog::open()
+                    .and_then(|mut catalog| catalog.save(&provider.name(), &provider.packages()))
+                    .unwrap_or_else(|err| {
+                        eprintln!(
+                            "Failed to cache the packages of {}: {err:?}",
+                            provider.name()
+                        );
+                        SystemTime::now()
+                    });
+                if !awaiting.is_empty() {
+                    let packages = provider.packages();
+                    let recorded = History::open().and_then(|mut history| {
+                        awaiting
+                            .iter()
+                            .try_for_each(|id| history.record_versions_after(*id, &packages))
+                    });
+                    if let Err(err) = recorded {
+                        eprintln!(
+                            "Failed to record the versions left by the transactions of {}: {err:?}",
+                            provider.name()
+                        );
+                    }
+                }
+                let index = Arc::new(SearchIndex::new(provider.packages()));
+                (provider, refreshed, index)
+            });
+            let _ = sender.send_blocking(result);
+        });
+
+        glib::spawn_future_local(clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                let Ok(result) = receiver.recv().await else {
+                    return;
+                };
+                let name = item.name();
+                let selected = window.dropdown_text() == name;
+                let shown = selected || window.all_selected();
+                if result.is_err() {
+                    // Left for the next time it loads
+                    window
+                        .imp()
+                        .awaiting_versions
+                        .borrow_mut()
+                        .extend(unrecorded.into_iter().map(|id| (name.clone(), id)));
+                }
+                match result {
+                    Ok((provider, refreshed, index)) => {
+                        let obj = window.imp();
+                        obj.cached.borrow_mut().remove(&name);
+                        obj.refreshed.borrow_mut().insert(name.clone(), refreshed);
+                        obj.indexes.borrow_mut().insert(name.clone(), index);
+                        // Installing or removing changes what the details say
+                        obj.details_cache.borrow_mut().remove(&name);
+                        if let Some(current) = obj
+                            .providers
+                            .borrow_mut()
+                            .iter_mut()
+                            .find(|current| current.name() == name)
+                        {
+                            *current = provider;
+                        }
+                        item.set_status(ProviderStatus::Ready);
+                    }
+                    // Browsing the catalog is all that can be done offline, so it isn't an error
+                    Err(err @ ProviderError::Network(_))
+                        if window.imp().cached.borrow().contains(&name) =>
+                    {
+                        item.set_status(ProviderStatus::Offline(err.to_string()));
+                    }
+                    Err(err) => {
+                        item.set_status(ProviderStatus::Failed(err.to_string()));
+                        if selected {
+                            window.show_error(
+                                &format!("Failed to load {name}"),
+                                err.into(),
+                                Some(|window| window.load_provider(&window.dropdown_text())),
+                            );
+                        }
+                    }
+                }
+                window.refresh_all_item();
+                if shown {
+                    window.show_packages();
+                }
+            }
+        ));
     }
 
     async fn handle_update_all(&self) -> Result<()> {
         let obj = self.imp();
 
-        let mut password = obj.password.borrow().clone();
-
         let some_root_required = obj
             .providers
             .borrow()
             .iter()
             .any(|provider| provider.is_root_required());
-
-        if some_root_required && password.is_none() {
-            password = messagebox::ask_password(self).await;
-            obj.password.replace(password.clone());
-            if password.is_none() {
-                return Err(anyhow!("Failed to get password"));
-            }
-        }
+        let password = self
+            .password_for(some_root_required)
+            .await
+            .ok_or(ProviderError::Cancelled)?;
 
         self.goto_command()?;
 
+        // The other providers are still updated after one fails
         let providers = obj.providers.borrow().clone();
-        let count = providers.len();
+        let mut outcome = Status::Succeeded;
         for (index, provider) in providers.iter().enumerate() {
             obj.text_command_buffer.insert(
                 &mut obj.text_command_buffer.end_iter(),
                 &format!("\n::: {} :::\n", provider.name()),
             );
-            let stream = provider.update(password.clone())?;
-            let join_handle = self.write_command_page(index == 0, index == count - 1, stream);
-            let _ = join_handle.await;
+            let entries = self.update_entries(&provider.name());
+            let stream = match provider.update(Some(password.clone())) {
+                Ok(stream) => stream,
+                Err(err) => {
+                    self.abort_commands(index > 0)?;
+                    return Err(err.into());
+                }
+            };
+            let status = self
+                .write_command_page(index == 0, stream, provider, &entries)
+                .await
+                .unwrap_or(Status::Cancelled);
+            outcome = worse(outcome, status);
+            if status == Status::Cancelled {
+                break;
+            }
         }
+        self.finish_command(outcome);
 
         Ok(())
     }
 
-    fn handle_dropdown_changed(&self) -> Result<()> {
-        let obj = self.imp();
-
-        let dropdown_text = obj
+    fn handle_dropdown_changed(&self) {
+        // Nothing is selected while the providers are looked for
+        let Some(item) = self
+            .imp()
             .dropdown_provider
             .selected_item()
-            .and_downcast::<gtk::StringObject>()
-            .context("Failed to get current provider")?
-            .string();
+            .and_downcast::<ProviderObject>()
+        else {
+            return;
+        };
+        // Choosing a provider that failed to load tries again
+        if item.failed() {
+            self.load_provider(&item.name());
+        }
+        let obj = self.imp();
+        let all = self.all_selected();
+        obj.column_provider.set_visible(all);
+        obj.show_updates.set_sensitive(!all);
+        self.show_packages();
+    }
+
+    fn all_selected(&self) -> bool {
+        self.dropdown_text() == ALL_PROVIDERS
+    }
 
-        self.update_model(&dropdown_text)?;
+    /// Lists the packages of the selected provider, or of every provider, from the catalog until
+    /// they have loaded.
+    fn show_packages(&self) {
+        let obj = self.imp();
 
-        let store = self.provider().model()?;
-        let sorter = obj.column_view.sorter();
-        let model = gtk::SortListModel::new(Some(store), sorter);
-        obj.filter_list.set_model(Some(&model));
-        obj.single_selection.set_model(Some(&obj.filter_list));
+        let names: Vec<String> = if self.all_selected() {
+            obj.providers.borrow().iter().map(|p| p.name()).collect()
+        } else {
+            vec![self.dropdown_text()]
+        };
+        let listed = names
+            .into_iter()
+            .map(|provider| {
+                let index = obj
+                    .indexes
+                    .borrow()
+                    .get(&provider)
+                    .cloned()
+                    .unwrap_or_default();
+                let objects = index
+                    .packages()
+                    .iter()
+                    .map(|data| PackageObject::new(&provider, data.clone()))
+                    .collect();
+                Listed {
+                    provider,
+                    index,
+                    objects,
+                }
+            })
+            .collect();
+        obj.listed.replace(listed);
+        self.run_search();
+        self.refresh_refreshed_label();
 
         obj.header_bar.set_visible(true);
         let current_page = match obj.stack.visible_child_name() {
@@ -227,170 +871,1320 @@ impl Window {
             None => "splash".to_string(),
         };
         if current_page == "splash" {
-            let widget = self.page_by_name("main_page")?;
-            obj.stack.set_visible_child(&widget);
+            if let Ok(widget) = self.page_by_name("main_page") {
+                obj.stack.set_visible_child(&widget);
+            }
         }
-        Ok(())
     }
 
-    fn handle_selection_changed(&self, grid: &gtk::SingleSelection) -> Result<()> {
+    /// Tells how old the listed packages are and whether they are being refreshed.
+    fn refresh_refreshed_label(&self) {
         let obj = self.imp();
-
-        let item = grid
+        let Some(item) = obj
+            .dropdown_provider
             .selected_item()
-            .and_downcast::<PackageObject>()
-            .context("Failed to get item")?;
-        let provider = self.provider();
-        let info = provider.package_info(item.qualifiedName())?;
-        let buffer = gtk::TextBuffer::builder().text(info).build();
+            .and_downcast::<ProviderObject>()
+        else {
+            obj.refreshed_label.set_text("");
+            return;
+        };
+        if item.name() == ALL_PROVIDERS {
+            obj.refreshed_label
+                .set_text(&self.all_refreshed_text(&item));
+            return;
+        }
+        let text = match obj.refreshed.borrow().get(&item.name()) {
+            None if item.loading() => "Loading packages…".to_string(),
+            None => String::new(),
+            Some(&time) if item.offline() => {
+                format!("Offline, showing packages refreshed {}", time_ago(time))
+            }
+            Some(&time) if item.failed() => {
+                format!(
+                    "Refresh failed, showing packages refreshed {}",
+                    time_ago(time)
+                )
+            }
+            Some(&time) if item.loading() => {
+                format!("Refreshed {}, refreshing…", time_ago(time))
+            }
+            Some(&time) => format!("Refreshed {}", time_ago(time)),
+        };
+        obj.refreshed_label.set_text(&text);
+    }
 
-        obj.text_box.set_buffer(Some(&buffer));
-        obj.text_box.set_visible(true);
+    /// Sums up the providers, going by the one refreshed longest ago.
+    fn all_refreshed_text(&self, all: &ProviderObject) -> String {
+        let failed = self
+            .provider_items()
+            .iter()
+            .filter(|item| item.failed())
+            .count();
+        let oldest = self.imp().refreshed.borrow().values().min().copied();
+        let mut text = match oldest {
+            None if all.loading() => return "Loading packages…".to_string(),
+            None => String::new(),
+            Some(time) => format!("Oldest list refreshed {}", time_ago(time)),
+        };
+        if all.loading() {
+            text.push_str(", refreshing…");
+        }
+        if failed > 0 {
+            let plural = if failed == 1 { "" } else { "s" };
+            text = format!("{failed} provider{plural} failed to refresh. {text}");
+        }
+        text
+    }
+
+    fn handle_selection_changed(&self) -> Result<()> {
+        let obj = self.imp();
 
-        if item.installed() {
-            obj.action.set_label("Remove");
+        let items = self.selected_packages();
+        let label = if items.iter().all(|item| item.installed()) {
+            "Mark for Removal"
+        } else if items.iter().all(|item| !item.installed()) {
+            "Mark for Install"
         } else {
-            obj.action.set_label("Install");
+            "Mark Changes"
+        };
+        obj.action.set_label(label);
+        obj.action.set_sensitive(!items.is_empty());
+
+        // Whatever is still loading is for another selection now
+        let generation = obj.details_generation.get() + 1;
+        obj.details_generation.set(generation);
+        obj.details_spinner.set_visible(false);
+
+        match items.as_slice() {
+            [] => obj.details_pane.set_visible(false),
+            [item] => self.load_details(generation, &item.provider(), item.qualifiedName())?,
+            items => self.show_output(&format!("{} packages selected", items.len())),
         }
-        obj.action.set_sensitive(true);
 
         Ok(())
     }
 
-    async fn handle_action(&self) -> Result<()> {
+    /// Shows the details of `package` from the cache, or asks the worker for them.
+    fn load_details(&self, generation: u64, provider: &str, package: String) -> Result<()> {
         let obj = self.imp();
+        let cached = {
+            let mut cache = obj.details_cache.borrow_mut();
+            let recent = cache.entry(provider.to_string()).or_default();
+            recent
+                .iter()
+                .position(|(name, _)| *name == package)
+                .map(|position| {
+                    // Moved last as the most recently shown
+                    let entry = recent.remove(position);
+                    recent.push(entry.clone());
+                    entry.1
+                })
+        };
+        if let Some(details) = cached {
+            self.show_details(provider, details);
+            return Ok(());
+        }
 
-        let item = obj
-            .single_selection
-            .selected_item()
-            .and_downcast::<PackageObject>()
-            .context("Failed to get item")?;
-        let action = obj
-            .action
-            .label()
-            .context("Unable to identify the action (Install or Remove)")?;
-        let password = self.password().await.context("Failed to get password")?;
-        self.goto_command()?;
+        let provider = self
+            .provider_named(provider)
+            .context(format!("Provider {provider} not found"))?
+            .clone();
+        self.show_output("");
+        obj.details_spinner.set_visible(true);
+        obj.details_requests
+            .get()
+            .context("The package details can't be loaded yet")?
+            .try_send(DetailsRequest {
+                generation,
+                provider,
+                package,
+            })?;
+        Ok(())
+    }
+
+    /// Fetches the details of the selected package off the main thread. Requests that were
+    /// replaced by a newer one while waiting are skipped.
+    fn setup_details_worker(&self) {
+        let (sender, requests) = unbounded::<DetailsRequest>();
+        let (results_sender, results) = unbounded();
+        spawn(move || {
+            while let Ok(mut request) = requests.recv_blocking() {
+                while let Ok(newer) = requests.try_recv() {
+                    request = newer;
+                }
+                let details = request.provider.package_details(request.package.clone());
+                let result = (
+                    request.generation,
+                    request.provider.name(),
+                    request.package,
+                    details,
+                );
+                if results_sender.send_blocking(result).is_err() {
+                    break;
+                }
+            }
+        });
+        // Weak, so the window can close while the worker waits
+        let window = self.downgrade();
+        glib::spawn_future_local(async move {
+            while let Ok((generation, provider, package, details)) = results.recv().await {
+                let Some(window) = window.upgrade() else {
+                    break;
+                };
+                window.handle_details_loaded(generation, provider, package, details);
+            }
+        });
+        let _ = self.imp().details_requests.set(sender);
+    }
+
+    fn handle_details_loaded(
+        &self,
+        generation: u64,
+        provider: String,
+        package: String,
+        details: Result<PackageDetails, ProviderError>,
+    ) {
+        let obj = self.imp();
+        if obj.details_generation.get() != generation {
+            return;
+        }
+        obj.details_spinner.set_visible(false);
+        match details {
+            Ok(details) => {
+                {
+                    let mut cache = obj.details_cache.borrow_mut();
+                    let recent = cache.entry(provider.clone()).or_default();
+                    if recent.len() >= DETAILS_CACHE_SIZE {
+                        recent.remove(0);
+                    }
+                    recent.push((package, details.clone()));
+                }
+                self.show_details(&provider, details);
+            }
+            Err(err) => self.show_error(
+                "Failed to show the package",
+                err.into(),
+                Some(|window| window.imp().selection.selection_changed(0, 0)),
+            ),
+        }
+    }
+
+    /// Lays out the details of a package of `provider`, on the output tab when the provider
+    /// printed nothing that could be told apart.
+    fn show_details(&self, provider: &str, details: PackageDetails) {
+        let obj = self.imp();
+        self.show_output(&details.raw);
+        if details.fields.is_empty()
+            && details.links.is_empty()
+            && details.dependencies.is_empty()
+            && details.release_notes.is_none()
+        {
+            return;
+        }
+
+        let grid = gtk::Grid::builder()
+            .column_spacing(12)
+            .row_spacing(6)
+            .build();
+        let fields = details
+            .fields
+            .iter()
+            .map(|(label, value)| (label, glib::markup_escape_text(value).to_string()));
+        let links = details.links.iter().map(|(label, url)| {
+            let url = glib::markup_escape_text(url);
+            (label, format!("<a href=\"{url}\">{url}</a>"))
+        });
+        for (row, (label, markup)) in fields.chain(links).enumerate() {
+            let label = gtk::Label::builder()
+                .label(label.as_str())
+                .xalign(0.0)
+                .yalign(0.0)
+                .css_classes(["dim-label"])
+                .build();
+            grid.attach(&label, 0, row as i32, 1, 1);
+            grid.attach(&details_value(&markup), 1, row as i32, 1, 1);
+        }
+        obj.details_box.append(&grid);
+
+        // Dependencies the provider lists link to them
+        let link = |dependency: &str| {
+            let name = details::dependency_name(dependency);
+            let text = glib::markup_escape_text(dependency);
+            if self.find_package(provider, name).is_some() {
+                let name = glib::markup_escape_text(name);
+                format!("<a href=\"{PACKAGE_SCHEME}{name}\">{text}</a>")
+            } else {
+                text.to_string()
+            }
+        };
+        if !details.dependencies.is_empty() {
+            let markup = details
+                .dependencies
+                .iter()
+                .map(|dependency| link(dependency))
+                .collect::<Vec<String>>()
+                .join(", ");
+            self.append_details_section("Dependencies", &markup, provider);
+        }
+        if !details.optional_dependencies.is_empty() {
+            let markup = details
+                .optional_dependencies
+                .iter()
+                .map(|(dependency, reason)| match reason.is_empty() {
+                    true => link(dependency),
+                    false => format!("{}: {}", link(dependency), glib::markup_escape_text(reason)),
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+            self.append_details_section("Optional Dependencies", &markup, provider);
+        }
+        if let Some(notes) = &details.release_notes {
+            let markup = glib::markup_escape_text(notes);
+            self.append_details_section("Release Notes", &markup, provider);
+        }
+        obj.details_stack.set_visible_child_name("details");
+    }
+
+    fn append_details_section(&self, title: &str, markup: &str, provider: &str) {
+        let obj = self.imp();
+        let heading = gtk::Label::builder()
+            .label(title)
+            .xalign(0.0)
+            .css_classes(["heading"])
+            .build();
+        let value = details_value(markup);
+        let provider = provider.to_string();
+        value.connect_activate_link(clone!(
+            #[weak(rename_to = window)]
+            self,
+            #[upgrade_or]
+            glib::Propagation::Proceed,
+            move |_, uri| match uri.strip_prefix(PACKAGE_SCHEME) {
+                Some(name) => {
+                    window.goto_package(&provider, name);
+                    glib::Propagation::Stop
+                }
+                None => glib::Propagation::Proceed,
+            }
+        ));
+        obj.details_box.append(&heading);
+        obj.details_box.append(&value);
+    }
+
+    /// Shows `text` on the output tab, emptying the details.
+    fn show_output(&self, text: &str) {
+        let obj = self.imp();
+        let buffer = gtk::TextBuffer::builder().text(text).build();
+        obj.text_box.set_buffer(Some(&buffer));
+        while let Some(child) = obj.details_box.first_child() {
+            obj.details_box.remove(&child);
+        }
+        obj.details_stack.set_visible_child_name("output");
+        obj.details_pane.set_visible(true);
+    }
+
+    /// The listed package of `provider` known as `name`, by its name or the end of its
+    /// qualified name, e.g. the ref of a Flatpak runtime.
+    fn find_package(&self, provider: &str, name: &str) -> Option<PackageObject> {
+        self.imp()
+            .listed
+            .borrow()
+            .iter()
+            .filter(|listed| listed.provider == provider)
+            .flat_map(|listed| listed.objects.iter())
+            .find(|object| is_named(object, name))
+            .cloned()
+    }
+
+    /// Searches for the package and selects it once found.
+    fn goto_package(&self, provider: &str, name: &str) {
+        let obj = self.imp();
+        obj.pending_selection
+            .replace(Some((provider.to_string(), name.to_string())));
+        let mut search = format!("\"{name}\"");
+        if self.all_selected() {
+            search = format!("provider:{provider} {search}");
+        }
+        if obj.search_entry.text() == search {
+            self.run_search();
+        } else {
+            obj.search_entry.set_text(&search);
+        }
+    }
+
+    fn handle_action(&self) -> Result<()> {
+        let obj = self.imp();
+
+        let items = self.selected_packages();
+        if items.is_empty() {
+            return Err(anyhow!("No package selected"));
+        }
 
-        let stream = match action.as_str() {
-            "Install" => self
-                .provider()
-                .install(Some(password), item.qualifiedName()),
-            "Remove" => self.provider().remove(Some(password), item.qualifiedName()),
-            _ => Err(anyhow!("Invalid Action. ")),
-        }?;
+        let mut pending = obj.pending_changes.borrow_mut();
+        for item in items {
+            let provider = item.provider();
+            let operation = if item.installed() {
+                Operation::Remove
+            } else {
+                Operation::Install
+            };
+            let package = item.qualifiedName();
+            pending.retain(|(name, change)| !(name == &provider && change.package == package));
+            pending.push((provider.clone(), Change { operation, package }));
+        }
+        drop(pending);
 
-        self.write_command_page(true, true, stream);
+        obj.selection.unselect_all();
+        self.refresh_pending_changes();
 
         Ok(())
     }
 
-    async fn handle_update(&self) -> Result<()> {
-        let password = self.password().await.context("Failed to get password")?;
+    async fn handle_apply_changes(&self) -> Result<()> {
+        let obj = self.imp();
+
+        let mut groups: Vec<(String, Vec<Change>)> = Vec::new();
+        for (provider, change) in obj.pending_changes.borrow().iter() {
+            match groups.iter_mut().find(|(name, _)| name == provider) {
+                Some((_, changes)) => changes.push(change.clone()),
+                None => groups.push((provider.clone(), vec![change.clone()])),
+            }
+        }
+        if groups.is_empty() {
+            return Ok(());
+        }
+
+        let providers = obj.providers.borrow().clone();
+        let providers = groups
+            .into_iter()
+            .map(|(name, changes)| {
+                let provider = providers
+                    .iter()
+                    .find(|provider| provider.name() == name)
+                    .context(format!("Provider {name} not found"))?;
+                Ok((provider.clone(), changes))
+            })
+            .collect::<Result<Vec<(ProviderKind, Vec<Change>)>>>()?;
+
+        let some_root_required = providers
+            .iter()
+            .any(|(provider, _)| provider.is_root_required());
+        let password = self
+            .password_for(some_root_required)
+            .await
+            .ok_or(ProviderError::Cancelled)?;
 
         self.goto_command()?;
+        let mut start = obj.text_command_buffer.start_iter();
+        let mut end = obj.text_command_buffer.end_iter();
+        obj.text_command_buffer.delete(&mut start, &mut end);
 
-        let stream = self.provider().update(Some(password))?;
-        self.write_command_page(true, true, stream);
+        // The changes of the other providers are still applied after one fails
+        let mut outcome = Status::Succeeded;
+        for (index, (provider, changes)) in providers.into_iter().enumerate() {
+            obj.text_command_buffer.insert(
+                &mut obj.text_command_buffer.end_iter(),
+                &format!("\n::: {} :::\n", provider.name()),
+            );
+            let entries = self.change_entries(&provider.name(), &changes);
+            let stream = match provider.execute(Some(password.clone()), changes) {
+                Ok(stream) => stream,
+                Err(err) => {
+                    self.abort_commands(index > 0)?;
+                    return Err(err.into());
+                }
+            };
+            // The changes that couldn't start stay in the basket
+            obj.pending_changes
+                .borrow_mut()
+                .retain(|(name, _)| *name != provider.name());
+            self.refresh_pending_changes();
+            let status = self
+                .write_command_page(false, stream, &provider, &entries)
+                .await
+                .unwrap_or(Status::Cancelled);
+            outcome = worse(outcome, status);
+            if status == Status::Cancelled {
+                break;
+            }
+        }
+        self.finish_command(outcome);
 
         Ok(())
     }
 
-    fn handle_info_bar_clicked(&self, _: &gtk::Button) -> Result<()> {
+    fn refresh_pending_changes(&self) {
         let obj = self.imp();
+        let pending = obj.pending_changes.borrow();
 
-        let widget = self.page_by_name("main_page")?;
-        obj.info_bar.set_visible(false);
+        let describe = |operation: Operation| {
+            pending
+                .iter()
+                .filter(|(_, change)| change.operation == operation)
+                .map(|(provider, change)| format!("{} ({provider})", change.package))
+                .collect::<Vec<String>>()
+        };
+        let parts: Vec<String> = [Operation::Install, Operation::Remove]
+            .into_iter()
+            .filter_map(|operation| {
+                let packages = describe(operation);
+                (!packages.is_empty()).then(|| format!("{operation} {}", packages.join(", ")))
+            })
+            .collect();
+
+        obj.pending_bar.set_visible(!pending.is_empty());
+        obj.pending_label
+            .set_text(&format!("Pending changes: {}", parts.join("; ")));
+    }
+
+    fn selected_packages(&self) -> Vec<PackageObject> {
+        let selection = &self.imp().selection;
+        let bitset = selection.selection();
+        (0..bitset.size())
+            .filter_map(|index| {
+                selection
+                    .item(bitset.nth(index as u32))
+                    .and_downcast::<PackageObject>()
+            })
+            .collect()
+    }
+
+    async fn handle_update(&self) -> Result<()> {
+        if self.all_selected() {
+            return self.handle_update_all().await;
+        }
+        let password = self.password().await.ok_or(ProviderError::Cancelled)?;
+
+        let provider = self.provider().clone();
+        let entries = self.update_entries(&provider.name());
+        let stream = provider.update(Some(password))?;
+        self.goto_command()?;
+        let status = self
+            .write_command_page(true, stream, &provider, &entries)
+            .await
+            .unwrap_or(Status::Cancelled);
+        self.finish_command(status);
+
+        Ok(())
+    }
+
+    async fn handle_show_updates(&self) -> Result<()> {
+        let obj = self.imp();
+
+        let provider = self.provider().clone();
+        let widget = self.page_by_name("updates_page")?;
         obj.stack.set_visible_child(&widget);
-        obj.search_entry.set_sensitive(true);
-        obj.update.set_sensitive(true);
-        obj.dropdown_provider.set_sensitive(true);
+        self.set_controls_sensitive(false);
+
+        while let Some(row) = obj.updates_list.first_child() {
+            obj.updates_list.remove(&row);
+        }
+        obj.pending_updates.borrow_mut().clear();
+        obj.updates_apply.set_sensitive(false);
+        obj.updates_label
+            .set_text(&format!("Checking updates for {}...", provider.name()));
+
+        let name = provider.name();
+        let (sender, receiver) = async_channel::bounded(1);
+        spawn(move || {
+            let _ = sender.send_blocking(provider.upgradable());
+        });
+        let packages = receiver.recv().await??;
+
+        obj.updates_label
+            .set_text(&format!("{} updates available for {name}", packages.len()));
+        for package in packages {
+            let label = format!(
+                "{}  {} → {}  ({})",
+                package.name,
+                package.installed_version.unwrap_or_default(),
+                package.available_version.unwrap_or_default(),
+                package.repository
+            );
+            let check = gtk::CheckButton::builder()
+                .label(label)
+                .active(true)
+                .build();
+            check.connect_toggled(clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |_| window.refresh_updates_apply()
+            ));
+            obj.updates_list.append(&check);
+            obj.pending_updates
+                .borrow_mut()
+                .push((check, package.qualified_name));
+        }
+        self.refresh_updates_apply();
 
         Ok(())
     }
 
-    fn handle_search(&self, search: &gtk::SearchEntry) -> Result<()> {
+    async fn handle_apply_updates(&self) -> Result<()> {
+        let packages: Vec<String> = self
+            .imp()
+            .pending_updates
+            .borrow()
+            .iter()
+            .filter(|(check, _)| check.is_active())
+            .map(|(_, package)| package.clone())
+            .collect();
+        if packages.is_empty() {
+            return Ok(());
+        }
+        let password = self.password().await.ok_or(ProviderError::Cancelled)?;
+
+        let provider = self.provider().clone();
+        let entries: Vec<Entry> = packages
+            .iter()
+            .map(|package| {
+                let before = self.installed_version(&provider.name(), package);
+                Entry::new(package, Action::Update, before)
+            })
+            .collect();
+        let stream = provider.update_selected(Some(password), packages)?;
+        self.goto_command()?;
+        let status = self
+            .write_command_page(true, stream, &provider, &entries)
+            .await
+            .unwrap_or(Status::Cancelled);
+        self.finish_command(status);
+
+        Ok(())
+    }
+
+    fn refresh_updates_apply(&self) {
+        let obj = self.imp();
+        let any_selected = obj
+            .pending_updates
+            .borrow()
+            .iter()
+            .any(|(check, _)| check.is_active());
+        obj.updates_apply.set_sensitive(any_selected);
+    }
+
+    fn handle_show_history(&self) -> Result<()> {
         let obj = self.imp();
+        if obj.running.borrow().is_some() {
+            return Err(anyhow!("Wait for the running command to finish"));
+        }
 
-        obj.single_selection.unselect_all();
-        let value = search.text();
-        let use_fuzzy = settings::get()?.fuzzy_search;
+        let transactions = History::open()?.transactions(HISTORY_LIMIT)?;
+        let widget = self.page_by_name("history_page")?;
+        obj.stack.set_visible_child(&widget);
+        self.set_controls_sensitive(false);
 
-        let filter = if use_fuzzy {
-            fuzzy_search(value)
+        while let Some(row) = obj.history_list.first_child() {
+            obj.history_list.remove(&row);
+        }
+        obj.history_transcript.buffer().set_text("");
+        obj.history_undo.set_sensitive(false);
+        obj.history_label.set_text(&match transactions.len() {
+            0 => "Nothing was installed, removed or updated from here yet".to_string(),
+            count => format!("{count} transactions, the latest first"),
+        });
+        for transaction in &transactions {
+            let label = gtk::Label::builder()
+                .label(format!(
+                    "{}  {}  {}  ({})",
+                    time_ago(transaction.started),
+                    transaction.provider,
+                    transaction_summary(transaction),
+                    status_text(transaction)
+                ))
+                .halign(gtk::Align::Start)
+                .ellipsize(pango::EllipsizeMode::End)
+                .margin_top(5)
+                .margin_bottom(5)
+                .build();
+            obj.history_list.append(&label);
+        }
+        obj.transactions.replace(transactions);
+
+        Ok(())
+    }
+
+    /// Shows what the transaction printed and whether it can be undone.
+    fn handle_history_selected(&self, row: Option<&gtk::ListBoxRow>) {
+        let obj = self.imp();
+        let transactions = obj.transactions.borrow();
+        let Some(transaction) = row.and_then(|row| transactions.get(row.index() as usize)) else {
+            obj.history_transcript.buffer().set_text("");
+            obj.history_undo.set_sensitive(false);
+            return;
+        };
+
+        let transcript = History::open()
+            .and_then(|history| history.transcript(transaction.id))
+            .unwrap_or_else(|err| format!("Failed to read the output: {err:?}"));
+        obj.history_transcript.buffer().set_text(&transcript);
+
+        let undo = transaction.undo();
+        let can_downgrade = self
+            .provider_named(&transaction.provider)
+            .is_some_and(|provider| provider.can_downgrade());
+        let (sensitive, tooltip) = if transaction.status == Status::Running {
+            (false, "It hasn't finished".to_string())
+        } else if !undo.changes.is_empty() || (!undo.downgrades.is_empty() && can_downgrade) {
+            (
+                true,
+                "Reverts the packages to the versions before it".to_string(),
+            )
+        } else if !undo.downgrades.is_empty() {
+            (
+                false,
+                format!("{} can't install older versions", transaction.provider),
+            )
         } else {
-            simple_search(value)
+            (false, "It didn't change any package".to_string())
         };
-        obj.filter_list.set_filter(Some(&filter));
+        obj.history_undo.set_sensitive(sensitive);
+        obj.history_undo.set_tooltip_text(Some(&tooltip));
+    }
+
+    /// Removes what the selected transaction installed, installs again what it removed and
+    /// downgrades what it updated, when the provider can.
+    async fn handle_history_undo(&self) -> Result<()> {
+        let obj = self.imp();
+
+        let transaction = obj
+            .history_list
+            .selected_row()
+            .and_then(|row| obj.transactions.borrow().get(row.index() as usize).cloned())
+            .context("No transaction selected")?;
+        let provider = self
+            .provider_named(&transaction.provider)
+            .context(format!("Provider {} not found", transaction.provider))?
+            .clone();
+        let name = provider.name();
+        let undo = transaction.undo();
+        let downgrades = match provider.can_downgrade() {
+            true => undo.downgrades,
+            false => Vec::new(),
+        };
+        if undo.changes.is_empty() && downgrades.is_empty() {
+            return Ok(());
+        }
+
+        let password = self
+            .password_for(provider.is_root_required())
+            .await
+            .ok_or(ProviderError::Cancelled)?;
+
+        self.goto_command()?;
+
+        let changing = !undo.changes.is_empty();
+        if changing {
+            let entries = self.change_entries(&name, &undo.changes);
+            let stream = match provider.execute(Some(password.clone()), undo.changes) {
+                Ok(stream) => stream,
+                Err(err) => {
+                    self.abort_commands(false)?;
+                    return Err(err.into());
+                }
+            };
+            let status = self
+                .write_command_page(true, stream, &provider, &entries)
+                .await
+                .unwrap_or(Status::Cancelled);
+            // Downgrading after a failure would leave the packages further from either state
+            if status != Status::Succeeded || downgrades.is_empty() {
+                self.finish_command(status);
+                return Ok(());
+            }
+        }
+        if !downgrades.is_empty() {
+            let entries: Vec<Entry> = downgrades
+                .iter()
+                .map(|(package, _)| {
+                    let before = self.installed_version(&name, package);
+                    Entry::new(package, Action::Downgrade, before)
+                })
+                .collect();
+            let stream = match provider.downgrade(Some(password), downgrades) {
+                Ok(stream) => stream,
+                Err(err) => {
+                    self.abort_commands(changing)?;
+                    return Err(err.into());
+                }
+            };
+            let status = self
+                .write_command_page(!changing, stream, &provider, &entries)
+                .await
+                .unwrap_or(Status::Cancelled);
+            self.finish_command(status);
+        }
+
         Ok(())
     }
 
-    fn update_model(&self, provider_name: &str) -> Result<()> {
-        let mut providers = self.imp().providers.borrow_mut();
-        let provider = providers
-            .iter_mut()
-            .find(|provider| provider.name().eq(&provider_name))
-            .context("Provider not found")?;
-        provider.update_packages()
+    fn handle_info_bar_clicked(&self, _: &gtk::Button) -> Result<()> {
+        self.imp().info_bar.set_visible(false);
+        self.goto_main()
+    }
+
+    fn handle_search(&self, search: &gtk::SearchEntry) -> Result<()> {
+        let obj = self.imp();
+
+        obj.selection.unselect_all();
+        // The list stays filtered by the last valid query while the error is shown
+        let query = match Query::parse(&search.text()) {
+            Ok(query) => query,
+            Err(err) => {
+                search.add_css_class("error");
+                obj.search_error.set_text(&err.to_string());
+                obj.search_error.set_visible(true);
+                return Ok(());
+            }
+        };
+        search.remove_css_class("error");
+        obj.search_error.set_visible(false);
+        obj.query.replace(query);
+        self.run_search();
+        Ok(())
     }
 
+    /// Lists the packages matching the query, best first. The search runs on a worker thread and
+    /// its results are dropped when another search started meanwhile.
+    fn run_search(&self) {
+        let obj = self.imp();
+        let generation = obj.search_generation.get() + 1;
+        obj.search_generation.set(generation);
+
+        let query = obj.query.borrow().clone();
+        if query.is_empty() {
+            let objects: Vec<PackageObject> = obj
+                .listed
+                .borrow()
+                .iter()
+                .flat_map(|listed| listed.objects.iter().cloned())
+                .collect();
+            self.set_package_model(objects);
+            return;
+        }
+        let fuzzy = settings::get().map_or(None, |settings| settings.fuzzy());
+        let indexes: Vec<(String, Arc<SearchIndex>)> = obj
+            .listed
+            .borrow()
+            .iter()
+            .map(|listed| (listed.provider.clone(), listed.index.clone()))
+            .collect();
+
+        let (sender, receiver) = async_channel::bounded(1);
+        spawn(move || {
+            let indexes = indexes
+                .iter()
+                .map(|(provider, index)| (provider.as_str(), index.as_ref()));
+            let _ = sender.send_blocking(search::find(indexes, &query, fuzzy));
+        });
+        glib::spawn_future_local(clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                let Ok(found) = receiver.recv().await else {
+                    return;
+                };
+                let obj = window.imp();
+                if obj.search_generation.get() != generation {
+                    return;
+                }
+                let objects: Vec<PackageObject> = {
+                    let listed = obj.listed.borrow();
+                    found
+                        .into_iter()
+                        .map(|(index, package)| listed[index].objects[package].clone())
+                        .collect()
+                };
+                window.set_package_model(objects);
+            }
+        ));
+    }
+
+    fn set_package_model(&self, objects: Vec<PackageObject>) {
+        let obj = self.imp();
+        obj.selection.unselect_all();
+        let store = gio::ListStore::from_iter(objects);
+        let sorter = obj.column_view.sorter();
+        let model = gtk::SortListModel::new(Some(store), sorter);
+        obj.filter_list.set_model(Some(&model));
+        obj.selection.set_model(Some(&obj.filter_list));
+
+        let Some((provider, name)) = obj.pending_selection.take() else {
+            return;
+        };
+        let position = (0..obj.selection.n_items()).find(|&position| {
+            obj.selection
+                .item(position)
+                .and_downcast::<PackageObject>()
+                .is_some_and(|object| object.provider() == provider && is_named(&object, &name))
+        });
+        if let Some(position) = position {
+            obj.selection.select_item(position, true);
+        }
+    }
+
+    /// Streams the output of a command of `provider` changing `entries`, recording it in the
+    /// history.
     fn write_command_page(
         &self,
         clean: bool,
-        finish: bool,
         mut stream: CommandStream,
-    ) -> glib::JoinHandle<()> {
+        provider: &ProviderKind,
+        entries: &[Entry],
+    ) -> glib::JoinHandle<Status> {
         let (sender, receiver) = unbounded();
+        let (status_sender, status_receiver) = async_channel::bounded(1);
         let obj = self.imp();
+        let progress = provider.progress_format();
+        let provider = provider.name();
+        let transaction = self.begin_transaction(&provider, entries);
+
+        let canceller = stream.canceller();
+        let responder = stream.responder();
+        obj.running.replace(Some(canceller.clone()));
+        obj.cancel_button.set_visible(true);
+        obj.cancel_button.set_sensitive(true);
 
         if clean {
             let mut start = obj.text_command_buffer.start_iter();
             let mut end = obj.text_command_buffer.end_iter();
             obj.text_command_buffer.delete(&mut start, &mut end);
+            obj.ansi_style.replace(ansi::Style::default());
+            obj.progress_mark.replace(None);
+            obj.command_search.set_text("");
         }
+        obj.command_progress.set_fraction(0.0);
+        obj.command_progress.set_text(Some(""));
+        let buffer = &obj.text_command_buffer;
+        let transcript_start = buffer.create_mark(None, &buffer.end_iter(), true);
 
         spawn(move || {
             for value in &mut stream {
                 let _ = sender.send_blocking(value);
             }
-            let message = match stream.close() {
-                Ok(_) => "Command completed successfully. ".to_string(),
-                Err(err) => format!("{err:?}\nCommand ended with failure. "),
+            let (message, status, exit_code) = match stream.close().map_err(ProviderError::from) {
+                Ok(Outcome::Success) => (
+                    "Command completed successfully. ".to_string(),
+                    Status::Succeeded,
+                    Some(0),
+                ),
+                Ok(Outcome::Cancelled) | Err(ProviderError::Cancelled) => {
+                    ("Command cancelled. ".to_string(), Status::Cancelled, None)
+                }
+                // stderr was already streamed, so only the exit code is repeated
+                Err(ProviderError::CommandFailed {
+                    code: Some(code), ..
+                }) => (
+                    format!("Command ended with failure (exit code {code}). "),
+                    Status::Failed,
+                    Some(code),
+                ),
+                Err(err) => {
+                    let reason = err.to_string();
+                    let reason = reason.lines().next().unwrap_or_default();
+                    let message = match err.hint() {
+                        Some(hint) => format!("Command ended with failure: {reason}. {hint} "),
+                        None => format!("Command ended with failure: {reason}. "),
+                    };
+                    (message, Status::Failed, None)
+                }
             };
-            let _ = sender.send_blocking(message);
+            let _ = sender.send_blocking(Line::Stdout(message));
+            let _ = status_sender.send_blocking((status, exit_code));
         });
 
         glib::spawn_future_local(clone!(
             #[weak(rename_to = window)]
             self,
+            #[upgrade_or]
+            Status::Cancelled,
             async move {
+                // Pulse until the output reports its progress
+                let mut recognised = false;
                 while let Ok(result) = receiver.recv().await {
-                    let obj = window.imp();
-
-                    obj.text_command_buffer.insert(
-                        &mut obj.text_command_buffer.end_iter(),
-                        &format!("{result}\n"),
-                    );
+                    window.append_command_line(&result);
+                    let bar = &window.imp().command_progress;
+                    match progress.parse(result.text()) {
+                        Some(progress) => {
+                            recognised = true;
+                            bar.set_fraction(progress.fraction);
+                            bar.set_text(Some(&progress.label()));
+                        }
+                        None if !recognised => {
+                            bar.pulse();
+                            bar.set_text(Some("Working…"));
+                        }
+                        None => {}
+                    }
+                    if let Line::Prompt(prompt) = &result {
+                        window.answer_prompt(prompt, &responder, &canceller).await;
+                    }
                 }
-                if !finish {
-                    return;
+                let (status, exit_code) = status_receiver
+                    .recv()
+                    .await
+                    .unwrap_or((Status::Failed, None));
+                let buffer = &window.imp().text_command_buffer;
+                let transcript = buffer.text(
+                    &buffer.iter_at_mark(&transcript_start),
+                    &buffer.end_iter(),
+                    false,
+                );
+                buffer.delete_mark(&transcript_start);
+                if let Some(id) = transaction {
+                    window.finish_transaction(&provider, id, status, exit_code, &transcript);
+                }
+
+                if canceller.is_cancelled() {
+                    Status::Cancelled
+                } else {
+                    status
                 }
-                let obj = window.imp();
-                obj.info_bar.set_visible(true);
-                obj.info_bar_label.set_text("Finished.  ");
-                let _ = window.handle_dropdown_changed();
             }
         ))
     }
 
+    /// Leaves the command page after a command couldn't start: back to the list when none ran
+    /// before it, otherwise ending the ones that did as failed.
+    fn abort_commands(&self, ran: bool) -> Result<()> {
+        if ran {
+            self.finish_command(Status::Failed);
+            Ok(())
+        } else {
+            self.goto_main()
+        }
+    }
+
+    /// Ends the command page once its last command is done, telling how the commands went.
+    fn finish_command(&self, status: Status) {
+        let obj = self.imp();
+        obj.running.replace(None);
+        obj.cancel_button.set_visible(false);
+        obj.info_bar.set_visible(true);
+        let label = match status {
+            Status::Failed => "Failed",
+            Status::Cancelled => "Cancelled",
+            Status::Succeeded | Status::Running => {
+                obj.command_progress.set_fraction(1.0);
+                "Finished"
+            }
+        };
+        obj.command_progress.set_text(Some(label));
+        let unrecorded = match obj.history_failed.replace(false) {
+            true => " It couldn't be recorded in the history.",
+            false => "",
+        };
+        obj.info_bar_label
+            .set_text(&format!("{label}.{unrecorded}  "));
+        // The versions the transactions left are recorded once their providers reload
+        let awaiting: Vec<String> = obj
+            .awaiting_versions
+            .borrow()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in awaiting {
+            self.load_provider(&name);
+        }
+        self.load_provider(&self.dropdown_text());
+    }
+
+    /// Records in the history that a command of `provider` started, logging why it couldn't.
+    fn begin_transaction(&self, provider: &str, entries: &[Entry]) -> Option<i64> {
+        History::open()
+            .and_then(|mut history| history.begin(provider, entries))
+            .inspect_err(|err| {
+                eprintln!("Failed to record the transaction of {provider}: {err:?}");
+                self.imp().history_failed.set(true);
+            })
+            .ok()
+    }
+
+    fn finish_transaction(
+        &self,
+        provider: &str,
+        id: i64,
+        status: Status,
+        exit_code: Option<i32>,
+        transcript: &str,
+    ) {
+        let finished =
+            History::open().and_then(|history| history.finish(id, status, exit_code, transcript));
+        if let Err(err) = finished {
+            eprintln!("Failed to record the end of transaction {id}: {err:?}");
+            self.imp().history_failed.set(true);
+        }
+        self.imp()
+            .awaiting_versions
+            .borrow_mut()
+            .push((provider.to_string(), id));
+    }
+
+    /// The installed version of the package of `provider`, as it was last loaded.
+    fn installed_version(&self, provider: &str, package: &str) -> Option<String> {
+        self.imp()
+            .indexes
+            .borrow()
+            .get(provider)?
+            .packages()
+            .iter()
+            .find(|data| data.qualified_name == package)
+            .and_then(|data| data.installed_version.clone())
+    }
+
+    fn change_entries(&self, provider: &str, changes: &[Change]) -> Vec<Entry> {
+        changes
+            .iter()
+            .map(|change| {
+                let before = self.installed_version(provider, &change.package);
+                Entry::new(&change.package, change.operation.into(), before)
+            })
+            .collect()
+    }
+
+    /// The packages an update of the whole provider is expected to change.
+    fn update_entries(&self, provider: &str) -> Vec<Entry> {
+        self.imp()
+            .indexes
+            .borrow()
+            .get(provider)
+            .map(|index| {
+                index
+                    .packages()
+                    .iter()
+                    .filter(|data| data.status == PackageStatus::UpdateAvailable)
+                    .map(|data| {
+                        Entry::new(
+                            &data.qualified_name,
+                            Action::Update,
+                            data.installed_version.clone(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn append_command_line(&self, line: &Line) {
+        let obj = self.imp();
+        let buffer = &obj.text_command_buffer;
+
+        // A progress line is rewritten in place until the line ending it arrives
+        let progress_mark = obj.progress_mark.borrow().clone();
+        if let Some(mark) = &progress_mark {
+            if matches!(line, Line::Stdout(_) | Line::Progress(_)) {
+                buffer.delete(&mut buffer.iter_at_mark(mark), &mut buffer.end_iter());
+            }
+        }
+        match line {
+            Line::Progress(_) if progress_mark.is_none() => {
+                let mark = buffer.create_mark(None, &buffer.end_iter(), true);
+                obj.progress_mark.replace(Some(mark));
+            }
+            Line::Progress(_) => {}
+            _ => {
+                if let Some(mark) = obj.progress_mark.take() {
+                    buffer.delete_mark(&mark);
+                }
+            }
+        }
+
+        let stream_tag = match line {
+            Line::Stderr(_) | Line::Prompt(_) => Some(self.command_tag("stderr", |tag| {
+                tag.set_foreground(Some("#e01b24"));
+            })),
+            _ => None,
+        };
+        let mut style = obj.ansi_style.borrow_mut();
+        for (text, span_style) in ansi::parse(line.text(), &mut style) {
+            let mut tags = self.style_tags(&span_style);
+            tags.extend(stream_tag.clone());
+            let tags: Vec<&gtk::TextTag> = tags.iter().collect();
+            buffer.insert_with_tags(&mut buffer.end_iter(), &text, &tags);
+        }
+        buffer.insert(&mut buffer.end_iter(), "\n");
+    }
+
+    /// Looks up a tag of the command page, creating it on first use.
+    fn command_tag(&self, name: &str, setup: impl FnOnce(&gtk::TextTag)) -> gtk::TextTag {
+        let table = self.imp().text_command_buffer.tag_table();
+        if let Some(tag) = table.lookup(name) {
+            return tag;
+        }
+        let tag = gtk::TextTag::new(Some(name));
+        setup(&tag);
+        table.add(&tag);
+        tag
+    }
+
+    fn style_tags(&self, style: &ansi::Style) -> Vec<gtk::TextTag> {
+        let mut tags = Vec::new();
+        if style.bold {
+            tags.push(self.command_tag("ansi-bold", |tag| tag.set_weight(700)));
+        }
+        if style.dim {
+            tags.push(self.command_tag("ansi-dim", |tag| tag.set_weight(300)));
+        }
+        if style.italic {
+            tags.push(self.command_tag("ansi-italic", |tag| tag.set_style(pango::Style::Italic)));
+        }
+        if style.underline {
+            tags.push(self.command_tag("ansi-underline", |tag| {
+                tag.set_underline(pango::Underline::Single)
+            }));
+        }
+        if let Some(color) = style.foreground {
+            let color = color.hex();
+            tags.push(self.command_tag(&format!("ansi-fg-{color}"), |tag| {
+                tag.set_foreground(Some(&color))
+            }));
+        }
+        if let Some(color) = style.background {
+            let color = color.hex();
+            tags.push(self.command_tag(&format!("ansi-bg-{color}"), |tag| {
+                tag.set_background(Some(&color))
+            }));
+        }
+        tags
+    }
+
+    /// Highlights every match of the search entry and selects the next (or previous) one after
+    /// the current selection, wrapping around the output.
+    fn handle_command_search(&self, forward: bool) {
+        let obj = self.imp();
+        let buffer = &obj.text_command_buffer;
+        let matched = self.command_tag("search-match", |tag| {
+            tag.set_background(Some("#f6d32d"));
+            tag.set_foreground(Some("#000000"));
+        });
+        buffer.remove_tag(&matched, &buffer.start_iter(), &buffer.end_iter());
+
+        let text = obj.command_search.text();
+        if text.is_empty() {
+            return;
+        }
+        let flags = gtk::TextSearchFlags::CASE_INSENSITIVE;
+
+        let mut iter = buffer.start_iter();
+        while let Some((start, end)) = iter.forward_search(&text, flags, None) {
+            buffer.apply_tag(&matched, &start, &end);
+            iter = end;
+        }
+
+        let (selection_start, selection_end) = buffer.selection_bounds().unwrap_or_else(|| {
+            let cursor = buffer.iter_at_mark(&buffer.get_insert());
+            (cursor, cursor)
+        });
+        let found = if forward {
+            selection_end
+                .forward_search(&text, flags, None)
+                .or_else(|| buffer.start_iter().forward_search(&text, flags, None))
+        } else {
+            selection_start
+                .backward_search(&text, flags, None)
+                .or_else(|| buffer.end_iter().backward_search(&text, flags, None))
+        };
+        if let Some((start, end)) = found {
+            buffer.select_range(&start, &end);
+            let mut start = start;
+            obj.text_command
+                .scroll_to_iter(&mut start, 0.0, true, 0.0, 0.5);
+        }
+    }
+
+    fn command_output(&self) -> GString {
+        let buffer = &self.imp().text_command_buffer;
+        buffer.text(&buffer.start_iter(), &buffer.end_iter(), false)
+    }
+
+    fn handle_command_copy(&self) {
+        self.clipboard().set_text(&self.command_output());
+    }
+
+    async fn handle_command_save(&self) -> Result<()> {
+        let dialog = gtk::FileDialog::builder()
+            .title("Save Transcript")
+            .initial_name("package-manager.log")
+            .modal(true)
+            .build();
+        let file = match dialog.save_future(Some(self)).await {
+            Ok(file) => file,
+            Err(err) if err.matches(gtk::DialogError::Dismissed) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        let path = file.path().context("Selected file has no local path")?;
+        std::fs::write(path, self.command_output().as_str())?;
+        Ok(())
+    }
+
+    /// Asks the user to answer a prompt, cancelling the command when the dialog is dismissed.
+    async fn answer_prompt(&self, prompt: &str, responder: &Responder, canceller: &Canceller) {
+        let buffer = &self.imp().text_command_buffer;
+        let output = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+        let lines: Vec<&str> = output.lines().collect();
+        let context = lines[lines.len().saturating_sub(PROMPT_CONTEXT_LINES)..].join("\n");
+
+        let answer = messagebox::ask_prompt(self, &context, prompt).await;
+        let result = match answer {
+            Some(answer) => responder.answer(&answer),
+            None => {
+                canceller.cancel();
+                Ok(())
+            }
+        };
+        if let Err(err) = result {
+            self.show_error("Failed to answer the command", err, None);
+            canceller.cancel();
+        }
+    }
+
+    /// Shows `err` with the recoveries suited to it, `retry` runs the failed action again.
+    fn show_error(&self, title: &str, err: anyhow::Error, retry: Option<fn(&Window)>) {
+        let err = ProviderError::from(err);
+        if matches!(err, ProviderError::AuthFailed) {
+            self.forget_password();
+        }
+        let title = title.to_string();
+        glib::spawn_future_local(clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                let recovery = messagebox::error(&title, &err, retry.is_some(), &window).await;
+                match recovery {
+                    Some(Recovery::Retry) => {
+                        if let Some(retry) = retry {
+                            retry(&window);
+                        }
+                    }
+                    Some(Recovery::Reload) => window.load_provider(&window.dropdown_text()),
+                    Some(Recovery::FindProviders) => window.setup_data(),
+                    Some(Recovery::CopyDetails) => {
+                        window.clipboard().set_text(&format!("{title}\n{err}"));
+                    }
+                    None => {}
+                }
+            }
+        ));
+    }
+
+    fn handle_cancel(&self) {
+        let obj = self.imp();
+        if let Some(canceller) = obj.running.borrow().as_ref() {
+            canceller.cancel();
+            obj.cancel_button.set_sensitive(false);
+        }
+    }
+
     fn provider<'a>(&'a self) -> Ref<'a, ProviderKind> {
+        self.provider_named(&self.dropdown_text()).unwrap() // TODO remover
+    }
+
+    fn provider_named(&self, name: &str) -> Option<Ref<'_, ProviderKind>> {
         let providers = self.imp().providers.borrow();
-        Ref::map(providers, |providers| {
-            providers
-                .iter()
-                .find(|provider| provider.name().eq(&self.dropdown_text()))
-                .unwrap() // TODO remover
+        Ref::filter_map(providers, |providers| {
+            providers.iter().find(|provider| provider.name() == name)
         })
+        .ok()
     }
 
     fn dropdown_text(&self) -> String {
@@ -398,40 +2192,99 @@ impl Window {
             .imp()
             .dropdown_provider
             .selected_item()
-            .and_downcast::<gtk::StringObject>()
+            .and_downcast::<ProviderObject>()
         {
-            Some(value) => value.string().to_string(),
+            Some(value) => value.name(),
             None => "".to_string(),
         }
     }
 
     async fn password(&self) -> Option<SecVec<u8>> {
+        let is_root_required = self.provider().is_root_required();
+        self.password_for(is_root_required).await
+    }
+
+    /// Asks for the password only when sudo needs it, keeping it for the next commands.
+    async fn password_for(&self, root_required: bool) -> Option<SecVec<u8>> {
         let obj = self.imp();
         let password = obj.password.borrow().clone();
         if password.is_some() {
             return password;
         }
-        let is_root_required = self.provider().is_root_required();
-        if is_root_required {
+        if root_required && Privilege::current().needs_password() {
             let password = messagebox::ask_password(self).await;
-            obj.password.replace(password.clone());
+            self.store_password(password.clone());
             password
         } else {
             Some(SecStr::from(""))
         }
     }
 
-    pub fn goto_command(&self) -> Result<()> {
+    /// Keeps the password for the next commands, until the timeout in the settings expires.
+    fn store_password(&self, password: Option<SecVec<u8>>) {
         let obj = self.imp();
+        if let Some(source) = obj.password_expiry.take() {
+            source.remove();
+        }
+        let stored = password.is_some();
+        obj.password.replace(password);
+        self.set_forget_password_enabled(stored);
+
+        let timeout = settings::get().map_or(0, |settings| settings.password_timeout);
+        if stored && timeout > 0 {
+            let source = glib::timeout_add_seconds_local_once(
+                timeout * 60,
+                clone!(
+                    #[weak(rename_to = window)]
+                    self,
+                    move || {
+                        // The source is removed once it ran
+                        window.imp().password_expiry.take();
+                        window.forget_password();
+                    }
+                ),
+            );
+            obj.password_expiry.replace(Some(source));
+        }
+    }
 
+    fn forget_password(&self) {
+        self.store_password(None);
+        Privilege::current().reset();
+    }
+
+    fn set_forget_password_enabled(&self, enabled: bool) {
+        if let Some(action) = self
+            .lookup_action("forget-password")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_enabled(enabled);
+        }
+    }
+
+    pub fn goto_command(&self) -> Result<()> {
         let widget = self.page_by_name("command_page")?;
-        obj.stack.set_visible_child(&widget);
-        obj.search_entry.set_sensitive(false);
-        obj.update.set_sensitive(false);
-        obj.dropdown_provider.set_sensitive(false);
+        self.imp().stack.set_visible_child(&widget);
+        self.set_controls_sensitive(false);
+        Ok(())
+    }
+
+    fn goto_main(&self) -> Result<()> {
+        let widget = self.page_by_name("main_page")?;
+        self.imp().stack.set_visible_child(&widget);
+        self.set_controls_sensitive(true);
         Ok(())
     }
 
+    fn set_controls_sensitive(&self, sensitive: bool) {
+        let obj = self.imp();
+        obj.search_entry.set_sensitive(sensitive);
+        obj.update.set_sensitive(sensitive);
+        obj.show_updates
+            .set_sensitive(sensitive && !self.all_selected());
+        obj.dropdown_provider.set_sensitive(sensitive);
+    }
+
     fn page_by_name(&self, name: &str) -> Result<gtk::Widget> {
         self.imp()
             .stack
@@ -440,6 +2293,28 @@ impl Window {
     }
 }
 
+/// A value of the details pane, links included.
+fn details_value(markup: &str) -> gtk::Label {
+    gtk::Label::builder()
+        .label(markup)
+        .use_markup(true)
+        .wrap(true)
+        .wrap_mode(pango::WrapMode::WordChar)
+        .selectable(true)
+        .xalign(0.0)
+        .hexpand(true)
+        .build()
+}
+
+fn is_named(object: &PackageObject, name: &str) -> bool {
+    let qualified_name = object.qualifiedName();
+    object.name() == name
+        || qualified_name == name
+        || qualified_name
+            .strip_suffix(name)
+            .is_some_and(|start| start.ends_with([' ', '/']))
+}
+
 fn sorter_string_package(name: &str) -> gtk::StringSorter {
     gtk::StringSorter::builder()
         .ignore_case(true)
@@ -451,6 +2326,16 @@ fn sorter_string_package(name: &str) -> gtk::StringSorter {
         .build()
 }
 
+fn sorter_numeric_package(name: &str) -> gtk::NumericSorter {
+    gtk::NumericSorter::builder()
+        .expression(gtk::PropertyExpression::new(
+            PackageObject::static_type(),
+            gtk::Expression::NONE,
+            name,
+        ))
+        .build()
+}
+
 fn sorter_installed_package() -> gtk::CustomSorter {
     gtk::CustomSorter::new(move |obj1, obj2| {
         let package_1 = obj1
@@ -467,30 +2352,90 @@ fn sorter_installed_package() -> gtk::CustomSorter {
     })
 }
 
-fn fuzzy_search(value: GString) -> gtk::CustomFilter {
-    gtk::CustomFilter::new(move |obj| {
-        if value.is_empty() {
-            true
-        } else if let Some(obj) = obj.downcast_ref::<PackageObject>() {
-            let prec = fuzzy_compare(&obj.qualifiedName().to_lowercase(), &value.to_lowercase());
-            prec >= 0.3
-        } else {
-            false
+/// The packages each provider had when it was last loaded, so they can be shown right away.
+fn cached_snapshots(providers: &[ProviderKind]) -> HashMap<String, Snapshot> {
+    let catalog = match Catalog::open() {
+        Ok(catalog) => catalog,
+        Err(err) => {
+            eprintln!("Failed to open the package catalog: {err:?}");
+            return HashMap::new();
         }
-    })
+    };
+    providers
+        .iter()
+        .filter_map(|provider| {
+            let name = provider.name();
+            match catalog.load(&name) {
+                Ok(snapshot) => snapshot.map(|snapshot| (name, snapshot)),
+                Err(err) => {
+                    eprintln!("Failed to read the cached packages of {name}: {err:?}");
+                    None
+                }
+            }
+        })
+        .collect()
 }
 
-fn simple_search(value: GString) -> gtk::CustomFilter {
-    let value = value.to_ascii_lowercase();
-    gtk::CustomFilter::new(move |obj| {
-        if value.is_empty() {
-            true
-        } else if let Some(obj) = obj.downcast_ref::<PackageObject>() {
-            obj.qualifiedName()
-                .to_lowercase()
-                .contains(&value.to_lowercase())
-        } else {
-            false
-        }
-    })
+/// The packages a transaction changed, e.g. `Update git 2.44.0-1 → 2.45.0-1, Install htop`.
+fn transaction_summary(transaction: &Transaction) -> String {
+    if transaction.entries.is_empty() {
+        return "Update".to_string();
+    }
+    let mut parts: Vec<String> = transaction
+        .entries
+        .iter()
+        .take(SUMMARY_PACKAGES)
+        .map(|entry| {
+            let action = match entry.action {
+                Action::Install => "Install",
+                Action::Remove => "Remove",
+                Action::Update => "Update",
+                Action::Downgrade => "Downgrade",
+            };
+            match (&entry.version_before, &entry.version_after) {
+                (Some(before), Some(after)) if before != after => {
+                    format!("{action} {} {before} → {after}", entry.package)
+                }
+                _ => format!("{action} {}", entry.package),
+            }
+        })
+        .collect();
+    let others = transaction.entries.len().saturating_sub(SUMMARY_PACKAGES);
+    if others > 0 {
+        parts.push(format!("{others} more"));
+    }
+    parts.join(", ")
+}
+
+/// How a run of commands went, as the worst of `a` and `b`.
+fn worse(a: Status, b: Status) -> Status {
+    match (a, b) {
+        (Status::Cancelled, _) | (_, Status::Cancelled) => Status::Cancelled,
+        (Status::Failed, _) | (_, Status::Failed) => Status::Failed,
+        _ => b,
+    }
+}
+
+fn status_text(transaction: &Transaction) -> String {
+    match (transaction.status, transaction.exit_code) {
+        (Status::Succeeded, _) => "succeeded".to_string(),
+        (Status::Failed, Some(code)) => format!("failed with exit code {code}"),
+        (Status::Failed, None) => "failed".to_string(),
+        (Status::Cancelled, _) => "cancelled".to_string(),
+        // The app closed before it ended, unless it is still running
+        (Status::Running, _) => "unfinished".to_string(),
+    }
+}
+
+/// How long ago `time` was, e.g. `5 minutes ago`.
+fn time_ago(time: SystemTime) -> String {
+    let seconds = time.elapsed().unwrap_or(Duration::ZERO).as_secs();
+    let (value, unit) = match seconds {
+        0..60 => return "just now".to_string(),
+        60..3600 => (seconds / 60, "minute"),
+        3600..86400 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+    let plural = if value == 1 { "" } else { "s" };
+    format!("{value} {unit}{plural} ago")
 }